@@ -0,0 +1,130 @@
+use std::io::IsTerminal;
+
+use crate::{formula::Combiner, line_error, Formula, Kind, Match, NumIter};
+
+/// Print every line with the matches used for its value under `formula` highlighted.
+///
+/// `part` picks which rules to explain: `"1"` only counts digits, anything else
+/// also counts spelled-out numbers.
+pub fn run(input: &str, part: Option<&str>, formula: &Formula) {
+    let digits_only = part == Some("1");
+    let colour = std::io::stdout().is_terminal();
+
+    let mut sum: u64 = 0;
+    let mut missing = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let matches: Vec<Match> = NumIter::new(line)
+            .spans()
+            .filter(|m| !digits_only || m.kind == Kind::Digit)
+            .collect();
+
+        let value = match formula.value(matches.iter().map(|m| m.value)) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("{}", line_error(i, e));
+                return;
+            }
+        };
+        let Some(value) = value else {
+            missing += 1;
+            println!("{:>5}: {}  => skipped (no digits)", i + 1, line);
+            continue;
+        };
+        let Some(total) = sum.checked_add(value) else {
+            eprintln!("total overflows at line {}", i + 1);
+            return;
+        };
+        sum = total;
+
+        if matches.is_empty() {
+            missing += 1;
+            println!("{:>5}: {}  => 0 (no digits)", i + 1, line);
+            continue;
+        }
+        println!(
+            "{:>5}: {}  => {}",
+            i + 1,
+            highlight(line, &used(&matches, formula.combiner), colour),
+            value
+        );
+    }
+
+    println!("Sum: {}", sum);
+    if missing > 0 {
+        println!("{} line(s) had no digits", missing);
+    }
+}
+
+/// The matches that went into a line's value.
+fn used(matches: &[Match], combiner: Combiner) -> Vec<Match> {
+    match combiner {
+        Combiner::FirstLast => vec![matches[0], matches[matches.len() - 1]],
+        Combiner::Concat | Combiner::Sum => matches.to_vec(),
+        Combiner::FirstN(n) => matches.iter().take(n).copied().collect(),
+    }
+}
+
+/// `line` with each of `used` marked, alternating colours so neighbours stand apart.
+fn highlight(line: &str, used: &[Match], colour: bool) -> String {
+    let mut out = String::with_capacity(line.len() + 16);
+    let mut current = 0;
+
+    for (i, c) in line.char_indices() {
+        let mut class = used
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| (m.start..m.end).contains(&i))
+            .fold(0u8, |class, (k, _)| class | 1 << (k % 2));
+        // Brackets can't show where two matches overlap, so merge them
+        if !colour {
+            class = class.min(1);
+        }
+
+        if class != current {
+            if current != 0 {
+                out.push_str(if colour { "\x1b[0m" } else { "]" });
+            }
+            if class != 0 {
+                out.push_str(match (colour, class) {
+                    (false, _) => "[",
+                    (true, 1) => "\x1b[1;32m",
+                    (true, 2) => "\x1b[1;36m",
+                    (true, _) => "\x1b[1;35m",
+                });
+            }
+            current = class;
+        }
+        out.push(c);
+    }
+
+    if current != 0 {
+        out.push_str(if colour { "\x1b[0m" } else { "]" });
+    }
+
+    out
+}
+
+#[test]
+fn highlight_plain() {
+    let spans = |line, combiner| {
+        let matches: Vec<_> = NumIter::new(line).spans().collect();
+        used(&matches, combiner)
+    };
+    let first_last = |line| spans(line, Combiner::FirstLast);
+
+    assert_eq!(
+        highlight("xtwone3four", &first_last("xtwone3four"), false),
+        "x[two]ne3[four]"
+    );
+    assert_eq!(
+        highlight("oneight", &first_last("oneight"), false),
+        "[oneight]"
+    );
+    assert_eq!(highlight("a7b", &first_last("a7b"), false), "a[7]b");
+
+    let all = spans("xtwone3four", Combiner::Sum);
+    assert_eq!(highlight("xtwone3four", &all, false), "x[twone3four]");
+    let two = spans("a1b2c3", Combiner::FirstN(2));
+    assert_eq!(highlight("a1b2c3", &two, false), "a[1]b[2]c3");
+}
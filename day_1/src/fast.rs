@@ -0,0 +1,157 @@
+//! Byte-level version of part 1 for very large inputs.
+//!
+//! Everything is scanned eight bytes at a time (SWAR). Finding where each line
+//! ends reads every byte once, but digits are only looked for from the front
+//! of the line and from the back, stopping at the first one found each way.
+//!
+//! The whole file is read into memory before any of this starts; `stream` is
+//! the one to use when that won't fit.
+
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+
+/// High bit set in every byte of `word` that is an ASCII digit.
+fn digit_mask(word: u64) -> u64 {
+    // Clear the high bits first so the additions can't carry into the next byte
+    let low = word & !HI;
+    let above_nine = low + LO * (0x7f - b'9' as u64);
+    let at_least_zero = low + LO * (0x80 - b'0' as u64);
+    at_least_zero & !above_nine & !word & HI
+}
+
+/// High bit set in every byte of `word` that is a `\n`.
+fn newline_mask(word: u64) -> u64 {
+    let x = word ^ (LO * b'\n' as u64);
+    !(((x & !HI) + !HI) | x) & HI
+}
+
+fn load(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    u64::from_le_bytes(buf)
+}
+
+fn find(bytes: &[u8], mask: fn(u64) -> u64) -> Option<usize> {
+    let chunks = bytes.chunks_exact(8);
+    let tail = chunks.len() * 8;
+
+    for (i, chunk) in chunks.enumerate() {
+        let m = mask(load(chunk));
+        if m != 0 {
+            return Some(i * 8 + m.trailing_zeros() as usize / 8);
+        }
+    }
+
+    // Padding bytes are zero, which never matches
+    let m = mask(load(&bytes[tail..]));
+    (m != 0).then(|| tail + m.trailing_zeros() as usize / 8)
+}
+
+fn rfind(bytes: &[u8], mask: fn(u64) -> u64) -> Option<usize> {
+    let chunks = bytes.rchunks_exact(8);
+    let head = bytes.len() % 8;
+
+    for (i, chunk) in chunks.enumerate() {
+        let m = mask(load(chunk));
+        if m != 0 {
+            let start = bytes.len() - (i + 1) * 8;
+            return Some(start + 7 - m.leading_zeros() as usize / 8);
+        }
+    }
+
+    let m = mask(load(&bytes[..head]));
+    (m != 0).then(|| 7 - m.leading_zeros() as usize / 8)
+}
+
+/// The part 1 calibration value of a single line, or `None` if it has no digits.
+pub fn calibration(line: &[u8]) -> Option<u32> {
+    let first = line[find(line, digit_mask)?] - b'0';
+    let last = line[rfind(line, digit_mask)?] - b'0';
+    Some(first as u32 * 10 + last as u32)
+}
+
+/// Part 1 over a whole buffer. Lines without digits count as 0.
+pub fn sum(mut input: &[u8]) -> u64 {
+    let mut sum = 0;
+
+    while !input.is_empty() {
+        let end = find(input, newline_mask).unwrap_or(input.len());
+        sum += calibration(&input[..end]).unwrap_or(0) as u64;
+        input = &input[(end + 1).min(input.len())..];
+    }
+
+    sum
+}
+
+/// `sum`, split into roughly equal chunks on line boundaries and run on `threads` threads.
+pub fn sum_parallel(input: &[u8], threads: usize) -> u64 {
+    let chunk_len = input.len().div_ceil(threads.max(1)).max(1);
+
+    let mut chunks = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let split = match rest.get(chunk_len..) {
+            Some(after) => find(after, newline_mask).map_or(rest.len(), |i| chunk_len + i + 1),
+            None => rest.len(),
+        };
+        let (chunk, after) = rest.split_at(split);
+        chunks.push(chunk);
+        rest = after;
+    }
+
+    std::thread::scope(|s| {
+        chunks
+            .into_iter()
+            .map(|chunk| s.spawn(move || sum(chunk)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    })
+}
+
+/// Run part 1 on the file at `path` with the byte-level scanner, reading it all into memory first.
+pub fn run(path: &str, threads: Option<usize>) {
+    let input = match std::fs::read(path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Couldn't read {}: {}", path, e);
+            return;
+        }
+    };
+
+    let threads = threads
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1);
+
+    println!("Part 1: {}", sum_parallel(&input, threads));
+}
+
+#[test]
+fn matches_part_1() {
+    let expected: u64 = crate::INPUT
+        .lines()
+        .map(|line| {
+            let mut digs = line.chars().filter_map(|c| c.to_digit(10));
+            let first = digs.next().unwrap_or(0);
+            let last = digs.next_back().unwrap_or(first);
+            (first * 10 + last) as u64
+        })
+        .sum();
+
+    assert_eq!(sum(crate::INPUT.as_bytes()), expected);
+    for threads in [1, 2, 3, 7, 64] {
+        assert_eq!(sum_parallel(crate::INPUT.as_bytes(), threads), expected);
+    }
+}
+
+#[test]
+fn calibration_edges() {
+    assert_eq!(calibration(b""), None);
+    assert_eq!(calibration(b"abc"), None);
+    assert_eq!(calibration(b"7"), Some(77));
+    assert_eq!(calibration(b"a1bcdefghijklmnop2q"), Some(12));
+    assert_eq!(calibration("é9ü/:0".as_bytes()), Some(90));
+    assert_eq!(calibration(b"12345678\r"), Some(18));
+    assert_eq!(sum(b"1a\n\nb2\r\nc3d4"), 11 + 22 + 34);
+}
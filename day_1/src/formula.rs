@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+/// How a line's digits are turned into its value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Combiner {
+    /// `first * 10 + last`, a lone digit being used as both.
+    #[default]
+    FirstLast,
+    /// Every digit in order, as one number.
+    Concat,
+    Sum,
+    /// The first `n` digits as one number, or all of them if there are fewer.
+    FirstN(usize),
+}
+
+/// What to do with a line that has no digits at all.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Missing {
+    Skip,
+    #[default]
+    Zero,
+    Error,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Formula {
+    pub combiner: Combiner,
+    pub missing: Missing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormulaError {
+    NoDigits,
+    Overflow,
+}
+
+impl Formula {
+    /// The value for one line's digits, or `None` if the line should be skipped.
+    pub fn value(&self, digits: impl Iterator<Item = u32>) -> Result<Option<u64>, FormulaError> {
+        let mut digits = digits.map(u64::from).peekable();
+
+        if digits.peek().is_none() {
+            return match self.missing {
+                Missing::Skip => Ok(None),
+                Missing::Zero => Ok(Some(0)),
+                Missing::Error => Err(FormulaError::NoDigits),
+            };
+        }
+
+        let value = match self.combiner {
+            Combiner::FirstLast => {
+                let first = digits.next().unwrap();
+                first * 10 + digits.last().unwrap_or(first)
+            }
+            Combiner::Concat => concat(digits)?,
+            Combiner::Sum => digits.sum(),
+            Combiner::FirstN(n) => concat(digits.take(n))?,
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Read `--combine <combiner>` and `--missing <skip|zero|error>` from `args`.
+    pub fn from_args(args: &[String]) -> Result<Formula, String> {
+        let mut formula = Formula::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--combine" => formula.combiner = value()?.parse()?,
+                "--missing" => formula.missing = value()?.parse()?,
+                _ => return Err(format!("Unknown argument {:?}", arg)),
+            }
+        }
+
+        Ok(formula)
+    }
+}
+
+fn concat(mut digits: impl Iterator<Item = u64>) -> Result<u64, FormulaError> {
+    digits.try_fold(0u64, |n, d| {
+        n.checked_mul(10)
+            .and_then(|n| n.checked_add(d))
+            .ok_or(FormulaError::Overflow)
+    })
+}
+
+impl FromStr for Combiner {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first-last" => Ok(Combiner::FirstLast),
+            "concat" => Ok(Combiner::Concat),
+            "sum" => Ok(Combiner::Sum),
+            _ => s
+                .strip_prefix("first:")
+                .and_then(|n| n.parse().ok())
+                .map(Combiner::FirstN)
+                .ok_or_else(|| format!("Unknown combiner {:?}", s)),
+        }
+    }
+}
+
+impl FromStr for Missing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(Missing::Skip),
+            "zero" => Ok(Missing::Zero),
+            "error" => Ok(Missing::Error),
+            _ => Err(format!("Unknown option for missing digits {:?}", s)),
+        }
+    }
+}
+
+#[test]
+fn combiners() {
+    let value = |combiner, digits: &[u32]| {
+        Formula {
+            combiner,
+            missing: Missing::Error,
+        }
+        .value(digits.iter().copied())
+    };
+
+    assert_eq!(value(Combiner::FirstLast, &[7]), Ok(Some(77)));
+    assert_eq!(value(Combiner::FirstLast, &[1, 2, 3]), Ok(Some(13)));
+    assert_eq!(value(Combiner::Concat, &[1, 0, 2]), Ok(Some(102)));
+    assert_eq!(value(Combiner::Sum, &[4, 5, 6]), Ok(Some(15)));
+    assert_eq!(value(Combiner::FirstN(2), &[4, 5, 6]), Ok(Some(45)));
+    assert_eq!(value(Combiner::FirstN(5), &[4, 5]), Ok(Some(45)));
+    assert_eq!(
+        value(Combiner::Concat, &[9; 20]),
+        Err(FormulaError::Overflow)
+    );
+    assert_eq!(value(Combiner::Sum, &[]), Err(FormulaError::NoDigits));
+}
+
+#[test]
+fn missing_digits() {
+    let value = |missing| {
+        Formula {
+            missing,
+            ..Default::default()
+        }
+        .value(std::iter::empty())
+    };
+
+    assert_eq!(value(Missing::Skip), Ok(None));
+    assert_eq!(value(Missing::Zero), Ok(Some(0)));
+    assert_eq!(value(Missing::Error), Err(FormulaError::NoDigits));
+}
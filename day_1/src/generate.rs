@@ -0,0 +1,184 @@
+use crate::{NumIter, NUMS};
+
+/// Small splitmix64 generator, so fixtures are reproducible from a seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+}
+
+/// A generated line along with what each part should make of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    pub line: String,
+    pub part_1: Option<u32>,
+    pub part_2: u32,
+}
+
+/// How many letters the end of `a` shares with the start of `b`, e.g. 1 for "two" and "one".
+fn overlap(a: &str, b: &str) -> Option<usize> {
+    (1..a.len().min(b.len())).find(|&k| a.ends_with(&b[..k]))
+}
+
+/// Build a line whose part 2 calibration value is `value` (0 to 99).
+///
+/// `density` is the chance, from 0 to 1, of each number being written so it
+/// overlaps a neighbouring word, like the "two" in "twone".
+pub fn generate(value: u32, density: f64, rng: &mut Rng) -> Fixture {
+    assert!(value < 100, "calibration values only have two digits");
+    let (first, last) = ((value / 10) as usize, (value % 10) as usize);
+
+    loop {
+        let mut line = String::new();
+        noise(&mut line, rng);
+
+        // Follow numbers with a word that starts inside them
+        overlapping(&mut line, first, density, rng);
+        for _ in 0..rng.below(4) {
+            noise(&mut line, rng);
+            overlapping(&mut line, rng.below(10), density, rng);
+        }
+        noise(&mut line, rng);
+
+        // Lead into the last number with a word that ends inside it
+        match pick_overlap(rng, |n| overlap(NUMS[n], NUMS[last])) {
+            Some((prev, k)) if rng.chance(density) => {
+                line.push_str(&NUMS[prev][..NUMS[prev].len() - k]);
+                line.push_str(NUMS[last]);
+            }
+            _ => number(&mut line, last, rng),
+        }
+        if rng.chance(0.5) {
+            noise(&mut line, rng);
+        }
+
+        // Noise can spell out numbers by accident, so check the line really works
+        let mut nums = NumIter::new(&line);
+        let found_first = nums.next();
+        let found_last = nums.last().or(found_first);
+        if found_first == Some(first as u32) && found_last == Some(last as u32) {
+            let mut digits = line.chars().filter_map(|c| c.to_digit(10));
+            let part_1 = digits
+                .next()
+                .map(|first| first * 10 + digits.next_back().unwrap_or(first));
+
+            return Fixture {
+                line,
+                part_1,
+                part_2: value,
+            };
+        }
+    }
+}
+
+fn pick_overlap(rng: &mut Rng, overlap: impl Fn(usize) -> Option<usize>) -> Option<(usize, usize)> {
+    let options: Vec<_> = (0..10).filter_map(|n| overlap(n).map(|k| (n, k))).collect();
+    (!options.is_empty()).then(|| options[rng.below(options.len())])
+}
+
+fn overlapping(line: &mut String, n: usize, density: f64, rng: &mut Rng) {
+    if rng.chance(density) {
+        if let Some((next, k)) = pick_overlap(rng, |m| overlap(NUMS[n], NUMS[m])) {
+            line.push_str(NUMS[n]);
+            line.push_str(&NUMS[next][k..]);
+            return;
+        }
+    }
+    number(line, n, rng);
+}
+
+fn number(line: &mut String, n: usize, rng: &mut Rng) {
+    if rng.chance(0.5) {
+        line.push_str(NUMS[n]);
+    } else {
+        line.push((b'0' + n as u8) as char);
+    }
+}
+
+fn noise(line: &mut String, rng: &mut Rng) {
+    for _ in 0..rng.below(4) {
+        line.push((b'a' + rng.below(26) as u8) as char);
+    }
+}
+
+/// Print `count` tab-separated fixtures: the line, its part 2 value and its part 1 value.
+pub fn run(args: &[String]) {
+    let Some(value) = args
+        .first()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|&v| v < 100)
+    else {
+        eprintln!("Usage: day_1 generate <0-99> [--count N] [--density P] [--seed S]");
+        return;
+    };
+
+    let mut count = 10;
+    let mut density = 0.5;
+    let mut seed = 0;
+
+    for pair in args[1..].chunks(2) {
+        let parsed = match pair {
+            [flag, n] if flag == "--count" => n.parse().map(|n| count = n).is_ok(),
+            [flag, p] if flag == "--density" => p.parse().map(|p| density = p).is_ok(),
+            [flag, s] if flag == "--seed" => s.parse().map(|s| seed = s).is_ok(),
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("Bad argument {:?}", pair.join(" "));
+            return;
+        }
+    }
+
+    let mut rng = Rng::new(seed);
+    for _ in 0..count {
+        let fixture = generate(value, density, &mut rng);
+        let part_1 = fixture.part_1.map_or("-".to_string(), |p| p.to_string());
+        println!("{}\t{}\t{}", fixture.line, fixture.part_2, part_1);
+    }
+}
+
+#[test]
+fn generated_lines_hit_target() {
+    let mut rng = Rng::new(2023);
+
+    for value in 0..100 {
+        for density in [0.0, 0.5, 1.0] {
+            let fixture = generate(value, density, &mut rng);
+
+            let mut nums = NumIter::new(&fixture.line);
+            let first = nums.next().unwrap();
+            let last = nums.last().unwrap_or(first);
+            assert_eq!(first * 10 + last, value, "{}", fixture.line);
+            assert_eq!(
+                fixture.part_1,
+                crate::fast::calibration(fixture.line.as_bytes())
+            );
+        }
+    }
+}
+
+#[test]
+fn overlaps() {
+    assert_eq!(overlap("two", "one"), Some(1));
+    assert_eq!(overlap("seven", "nine"), Some(1));
+    assert_eq!(overlap("eight", "three"), Some(1));
+    assert_eq!(overlap("one", "two"), None);
+}
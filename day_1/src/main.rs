@@ -1,72 +1,193 @@
+mod explain;
+mod fast;
+mod formula;
+mod generate;
+mod stream;
+
+use formula::{Formula, FormulaError, Missing};
+
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
-    part_1();
-    part_2();
-}
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-fn part_1() {
-    let sum: u32 = INPUT.lines().fold(0, |sum, line| {
-        sum + {
-            let mut digs = line.chars().filter_map(|c| c.to_digit(10));
-            let first = digs.next().unwrap_or(0);
-            let last = digs.last().unwrap_or(first);
-            first * 10 + last
+    match args.first().map(String::as_str) {
+        Some("explain") => {
+            let (part, rest) = positional(&args[1..]);
+            match Formula::from_args(rest) {
+                Ok(formula) => explain::run(INPUT, part, &formula),
+                Err(e) => eprintln!("{}", e),
+            }
         }
-    });
+        Some("fast") => match args.get(1) {
+            Some(path) => fast::run(path, args.get(2).and_then(|n| n.parse().ok())),
+            None => eprintln!("Usage: day_1 fast <path> [threads]"),
+        },
+        Some("generate") => generate::run(&args[1..]),
+        Some("stream") => {
+            let (path, rest) = positional(&args[1..]);
+            match Formula::from_args(rest) {
+                Ok(formula) => stream::run(path.unwrap_or("-"), &formula),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        _ => match Formula::from_args(&args) {
+            Ok(formula) => {
+                part_1(&formula);
+                part_2(&formula);
+            }
+            Err(e) => eprintln!("{}", e),
+        },
+    }
+}
 
-    println!("Part 1: {}", sum);
+/// Split off a leading argument that isn't a flag, leaving the formula's flags.
+fn positional(args: &[String]) -> (Option<&str>, &[String]) {
+    match args.first() {
+        Some(arg) if !arg.starts_with("--") => (Some(arg.as_str()), &args[1..]),
+        _ => (None, args),
+    }
 }
 
-fn part_2() {
-    let sum: u32 = INPUT.lines().fold(0, |sum, line| {
-        sum + {
-            let mut digs = NumIter { src: line };
-            let first = digs.next().unwrap_or(0);
-            let last = digs.last().unwrap_or(first);
-            first * 10 + last
-        }
+fn part_1(formula: &Formula) {
+    let sum = total(INPUT, formula, |line| {
+        line.chars().filter_map(|c| c.to_digit(10))
     });
 
-    println!("Part 2: {}", sum);
+    match sum {
+        Ok(sum) => println!("Part 1: {}", sum),
+        Err(e) => eprintln!("Part 1 failed: {}", e),
+    }
+}
+
+fn part_2(formula: &Formula) {
+    match total(INPUT, formula, NumIter::new) {
+        Ok(sum) => println!("Part 2: {}", sum),
+        Err(e) => eprintln!("Part 2 failed: {}", e),
+    }
+}
+
+/// Sum `formula` over every line, using `digits` to pull the digits out of each.
+fn total<'a, I: Iterator<Item = u32>>(
+    input: &'a str,
+    formula: &Formula,
+    digits: impl Fn(&'a str) -> I,
+) -> Result<u64, String> {
+    input
+        .lines()
+        .enumerate()
+        .try_fold(0, |sum, (i, line)| add_line(sum, i, formula, digits(line)))
+}
+
+/// `sum` plus the value of line `i` (counting from 0), given its `digits`.
+fn add_line(
+    sum: u64,
+    i: usize,
+    formula: &Formula,
+    digits: impl Iterator<Item = u32>,
+) -> Result<u64, String> {
+    let mut digits = digits.peekable();
+    if digits.peek().is_none() && formula.missing == Missing::Zero {
+        eprintln!("Warning: line {} has no digits, counting it as 0", i + 1);
+    }
+
+    let value = formula.value(digits).map_err(|e| line_error(i, e))?;
+    sum.checked_add(value.unwrap_or(0))
+        .ok_or_else(|| format!("total overflows at line {}", i + 1))
+}
+
+fn line_error(i: usize, e: FormulaError) -> String {
+    match e {
+        FormulaError::NoDigits => format!("line {} has no digits", i + 1),
+        FormulaError::Overflow => format!("value of line {} overflows", i + 1),
+    }
+}
+
+const NUMS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Digit,
+    Word,
+}
+
+/// A number found in a line, with the byte range it was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Match {
+    value: u32,
+    start: usize,
+    end: usize,
+    kind: Kind,
 }
 
 struct NumIter<'a> {
     src: &'a str,
+    offset: usize,
 }
 
-impl<'a> Iterator for NumIter<'a> {
-    type Item = u32;
+impl<'a> NumIter<'a> {
+    fn new(src: &'a str) -> NumIter<'a> {
+        NumIter { src, offset: 0 }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        while !self.src.is_empty() {
-            if let Some(n) = self.src.chars().next().and_then(|c| c.to_digit(10)) {
-                self.src = &self.src[1..];
-                return Some(n);
-            }
+    /// Yield the full `Match` for each number rather than just its value.
+    fn spans(self) -> Spans<'a> {
+        Spans(self)
+    }
 
-            const NUMS: [&str; 10] = [
-                "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-            ];
+    fn next_match(&mut self) -> Option<Match> {
+        while let Some(c) = self.src.chars().next() {
+            let start = self.offset;
+            let found = match c.to_digit(10) {
+                Some(n) => Some((n, c.len_utf8(), Kind::Digit)),
+                None => NUMS
+                    .iter()
+                    .position(|str| self.src.starts_with(str))
+                    .map(|n| (n as u32, NUMS[n].len(), Kind::Word)),
+            };
 
-            for (n, &str) in NUMS.iter().enumerate() {
-                if self.src.starts_with(str) {
-                    self.src = &self.src[1..];
-                    return Some(n as u32);
-                }
-            }
+            // Only step over one character so overlapping words ("oneight") are both found
+            self.src = &self.src[c.len_utf8()..];
+            self.offset += c.len_utf8();
 
-            self.src = &self.src[1..];
+            if let Some((value, len, kind)) = found {
+                return Some(Match {
+                    value,
+                    start,
+                    end: start + len,
+                    kind,
+                });
+            }
         }
 
         None
     }
 }
 
+impl Iterator for NumIter<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match().map(|m| m.value)
+    }
+}
+
+struct Spans<'a>(NumIter<'a>);
+
+impl Iterator for Spans<'_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_match()
+    }
+}
+
 #[test]
 fn num_iter() {
     fn nums(str: &str) -> Vec<u32> {
-        NumIter { src: str }.collect()
+        NumIter::new(str).collect()
     }
 
     assert_eq!(nums("two1nine"), vec![2, 1, 9]);
@@ -77,3 +198,47 @@ fn num_iter() {
     assert_eq!(nums("zoneight234"), vec![1, 8, 2, 3, 4]);
     assert_eq!(nums("7pqrstsixteen"), vec![7, 6]);
 }
+
+#[test]
+fn num_iter_spans() {
+    let spans: Vec<_> = NumIter::new("xtwone3").spans().collect();
+
+    assert_eq!(
+        spans,
+        vec![
+            Match {
+                value: 2,
+                start: 1,
+                end: 4,
+                kind: Kind::Word
+            },
+            Match {
+                value: 1,
+                start: 3,
+                end: 6,
+                kind: Kind::Word
+            },
+            Match {
+                value: 3,
+                start: 6,
+                end: 7,
+                kind: Kind::Digit
+            },
+        ]
+    );
+}
+
+#[test]
+fn total_overflow() {
+    let formula = Formula::from_args(&["--combine".to_string(), "concat".to_string()]).unwrap();
+    let nines = "9".repeat(19);
+
+    assert_eq!(
+        total(&format!("{}\n", nines), &formula, NumIter::new),
+        Ok(nines.parse().unwrap())
+    );
+    assert_eq!(
+        total(&format!("1\n{0}\n{0}\n", nines), &formula, NumIter::new),
+        Err("total overflows at line 3".to_string())
+    );
+}
@@ -0,0 +1,126 @@
+use std::io::{self, BufRead, BufReader};
+
+use crate::{add_line, Formula, NumIter};
+
+/// Running totals for both parts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub lines: u64,
+    pub part_1: u64,
+    pub part_2: u64,
+}
+
+/// Work through `reader` one line at a time, applying `formula` to each line
+/// and calling `progress` after it.
+///
+/// Only one line is held in memory at once. `\r\n` endings and a last line
+/// without a newline are both handled.
+pub fn process<R: BufRead>(
+    mut reader: R,
+    formula: &Formula,
+    mut progress: impl FnMut(&Totals),
+) -> Result<Totals, String> {
+    let mut totals = Totals::default();
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf);
+        if read.map_err(|e| e.to_string())? == 0 {
+            break;
+        }
+
+        let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        let i = totals.lines as usize;
+        let digits = line
+            .iter()
+            .filter(|b| b.is_ascii_digit())
+            .map(|b| u32::from(b - b'0'));
+        totals.part_1 = add_line(totals.part_1, i, formula, digits)?;
+        let line = String::from_utf8_lossy(line);
+        totals.part_2 = add_line(totals.part_2, i, formula, NumIter::new(&line))?;
+        totals.lines += 1;
+
+        progress(&totals);
+    }
+
+    Ok(totals)
+}
+
+/// Stream the file at `path` (or stdin for `-`), reporting totals every million lines.
+pub fn run(path: &str, formula: &Formula) {
+    const REPORT_EVERY: u64 = 1_000_000;
+
+    let report = |t: &Totals| {
+        if t.lines.is_multiple_of(REPORT_EVERY) {
+            eprintln!(
+                "{} lines: part 1 = {}, part 2 = {}",
+                t.lines, t.part_1, t.part_2
+            );
+        }
+    };
+
+    let result = if path == "-" {
+        process(io::stdin().lock(), formula, report)
+    } else {
+        std::fs::File::open(path)
+            .map_err(|e| e.to_string())
+            .and_then(|f| process(BufReader::new(f), formula, report))
+    };
+
+    match result {
+        Ok(totals) => {
+            println!("Part 1: {}", totals.part_1);
+            println!("Part 2: {}", totals.part_2);
+        }
+        Err(e) => eprintln!("Couldn't stream {}: {}", path, e),
+    }
+}
+
+#[test]
+fn line_endings() {
+    let totals = process(
+        &b"1abc2\r\npqr3stu8vwx\r\nxtwone3four"[..],
+        &Formula::default(),
+        |_| {},
+    )
+    .unwrap();
+
+    assert_eq!(
+        totals,
+        Totals {
+            lines: 3,
+            part_1: 12 + 38 + 33,
+            part_2: 12 + 38 + 24,
+        }
+    );
+}
+
+#[test]
+fn matches_input() {
+    let mut seen = 0;
+    let totals = process(crate::INPUT.as_bytes(), &Formula::default(), |t| {
+        seen = t.lines
+    })
+    .unwrap();
+
+    assert_eq!(seen, crate::INPUT.lines().count() as u64);
+    assert_eq!(totals.part_1, crate::fast::sum(crate::INPUT.as_bytes()));
+    assert_eq!(
+        Ok(totals.part_2),
+        crate::total(crate::INPUT, &Formula::default(), NumIter::new)
+    );
+
+    // Other formulas reach the stream too
+    let formula = Formula::from_args(&["--combine".to_string(), "sum".to_string()]).unwrap();
+    let totals = process(&b"1abc2\nthree4"[..], &formula, |_| {}).unwrap();
+    assert_eq!((totals.part_1, totals.part_2), (1 + 2 + 4, 1 + 2 + 3 + 4));
+
+    let formula = Formula::from_args(&["--missing".to_string(), "error".to_string()]).unwrap();
+    assert_eq!(
+        process(&b"12\nabc\n"[..], &formula, |_| {}),
+        Err("line 2 has no digits".to_string())
+    );
+}
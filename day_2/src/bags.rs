@@ -0,0 +1,180 @@
+use crate::{set::Set, Game};
+
+/// A candidate bag, named after where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bag {
+    pub name: String,
+    pub set: Set,
+}
+
+/// Read a bag written as `red=12,green=13,blue=14`.
+pub fn parse_bag(spec: &str) -> Result<Set, String> {
+    spec.split(',')
+        .map(|pair| {
+            let (col, num) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Expected <colour>=<count>, got {:?}", pair))?;
+            let num = num
+                .trim()
+                .parse()
+                .map_err(|_| format!("Bad count in {:?}", pair))?;
+            Ok((col.trim(), num))
+        })
+        .collect()
+}
+
+/// Read bags from a TOML file made of tables of colour counts:
+///
+/// ```toml
+/// [standard]
+/// red = 12
+/// green = 13
+/// blue = 14
+/// ```
+///
+/// Keys before the first table make up a bag named after the file.
+pub fn parse_bag_file(name: &str, contents: &str) -> Result<Vec<Bag>, String> {
+    let mut bags = vec![Bag {
+        name: name.to_string(),
+        set: Set::default(),
+    }];
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        let err = |msg: &str| format!("{}:{}: {}", name, i + 1, msg);
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            bags.push(Bag {
+                name: table.trim().trim_matches('"').to_string(),
+                set: Set::default(),
+            });
+            continue;
+        }
+
+        let (col, num) = line
+            .split_once('=')
+            .ok_or_else(|| err("expected `<colour> = <count>`"))?;
+        let num = num
+            .trim()
+            .parse()
+            .map_err(|_| err("count isn't a number"))?;
+        bags.last_mut().unwrap().set[col.trim().trim_matches('"')] = num;
+    }
+
+    // The nameless top-level bag only counts if something was put in it
+    if bags[0].set.colours().next().is_none() {
+        bags.remove(0);
+    }
+
+    Ok(bags)
+}
+
+/// Read `--bag <spec>` and `--bag-file <path>` arguments, each of which may be repeated.
+pub fn from_args(args: &[String]) -> Result<Vec<Bag>, String> {
+    let mut bags = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+        match arg.as_str() {
+            "--bag" => {
+                let value = value()?;
+                bags.push(Bag {
+                    name: value.clone(),
+                    set: parse_bag(value)?,
+                })
+            }
+            "--bag-file" => {
+                let value = value()?;
+                let contents = std::fs::read_to_string(value)
+                    .map_err(|e| format!("Couldn't read {}: {}", value, e))?;
+                bags.extend(parse_bag_file(value, &contents)?);
+            }
+            _ if !arg.starts_with("--") => return Err(format!("Unknown command {:?}", arg)),
+            _ => return Err(format!("Unknown argument {:?}", arg)),
+        }
+    }
+
+    Ok(bags)
+}
+
+/// The ids of every game that could have been played with `bag`.
+pub fn possible_games<'a>(games: &'a [Game], bag: &'a Set) -> impl Iterator<Item = u32> + 'a {
+    games
+        .iter()
+        .filter(|game| game.draws.iter().all(|set| set <= bag))
+        .map(|game| game.id)
+}
+
+pub fn run(games: &[Game], bags: &[Bag]) {
+    for bag in bags {
+        let ids: Vec<u32> = possible_games(games, &bag.set).collect();
+        let list: Vec<String> = ids.iter().map(u32::to_string).collect();
+
+        println!("Bag {}", bag.name);
+        println!("  Possible games: {}", list.join(", "));
+        println!(
+            "  Sum of game ids: {}",
+            ids.iter().map(|&id| u64::from(id)).sum::<u64>()
+        );
+    }
+}
+
+#[test]
+fn bag_specs() {
+    assert_eq!(
+        parse_bag("red=12, green = 13,blue=14"),
+        Ok(crate::default_bag())
+    );
+    assert!(parse_bag("red:12").is_err());
+    assert!(parse_bag("red=lots").is_err());
+
+    let args = |args: &[&str]| from_args(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+    assert_eq!(args(&["foo"]), Err("Unknown command \"foo\"".to_string()));
+    assert_eq!(
+        args(&["--nope", "1"]),
+        Err("Unknown argument \"--nope\"".to_string())
+    );
+    assert_eq!(args(&["--bag"]), Err("--bag needs a value".to_string()));
+    assert_eq!(
+        args(&["--bag", "red=1"]).unwrap()[0].set,
+        Set::from([("red", 1)])
+    );
+}
+
+#[test]
+fn bag_file() {
+    let bags = parse_bag_file(
+        "bags.toml",
+        "yellow = 2\n\n[standard] # the puzzle's bag\nred = 12\ngreen = 13\nblue = 14\n\n[\"tiny\"]\nred = 1\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        bags,
+        vec![
+            Bag {
+                name: "bags.toml".to_string(),
+                set: Set::from([("yellow", 2)]),
+            },
+            Bag {
+                name: "standard".to_string(),
+                set: crate::default_bag(),
+            },
+            Bag {
+                name: "tiny".to_string(),
+                set: Set::from([("red", 1)]),
+            },
+        ]
+    );
+
+    assert_eq!(
+        parse_bag_file("bags.toml", "[a]\nred 12"),
+        Err("bags.toml:2: expected `<colour> = <count>`".to_string())
+    );
+}
@@ -0,0 +1,139 @@
+use crate::{bags, set::Set, Game};
+
+/// A colour in one draw that needed more cubes than the bag has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Excess {
+    pub draw: usize,
+    pub colour: String,
+    pub count: u32,
+    pub limit: u32,
+}
+
+/// Every way in which `game` doesn't fit in `bag`. Empty if the game is possible.
+pub fn excesses(game: &Game, bag: &Set) -> Vec<Excess> {
+    game.draws
+        .iter()
+        .enumerate()
+        .flat_map(|(draw, set)| {
+            set.iter()
+                .filter(|&(col, num)| num > bag[col])
+                .map(move |(col, num)| Excess {
+                    draw,
+                    colour: col.to_string(),
+                    count: num,
+                    limit: bag[col],
+                })
+        })
+        .collect()
+}
+
+/// For each colour in `colours`, its minimum count for `game` and the first draw that needed it.
+///
+/// The draw is `None` for a colour that never came out of the bag.
+pub fn minimum_sources<'a>(
+    game: &Game,
+    colours: impl Iterator<Item = &'a str>,
+) -> Vec<(&'a str, u32, Option<usize>)> {
+    let min_bag = game.min_bag();
+
+    colours
+        .map(|col| {
+            let draw = game
+                .draws
+                .iter()
+                .position(|set| set[col] > 0 && set[col] == min_bag[col]);
+            (col, min_bag[col], draw)
+        })
+        .collect()
+}
+
+/// `explain [--bag <spec>] [--game <id>]`
+pub fn run(games: &[Game], args: &[String]) {
+    let mut bag = crate::default_bag();
+    let mut only: Option<u32> = None;
+
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, spec] if flag == "--bag" => match bags::parse_bag(spec) {
+                Ok(set) => bag = set,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            },
+            [flag, id] if flag == "--game" && id.parse::<u32>().is_ok() => only = id.parse().ok(),
+            _ => {
+                eprintln!("Bad argument {:?}", pair.join(" "));
+                return;
+            }
+        }
+    }
+
+    let no_cubes = crate::no_cubes(games);
+    let games: Vec<&Game> = games
+        .iter()
+        .filter(|game| only.is_none_or(|id| id == game.id))
+        .collect();
+
+    println!("Part 1, with a bag of {}:", bag);
+    for game in &games {
+        let excesses = excesses(game, &bag);
+        if excesses.is_empty() {
+            continue;
+        }
+
+        println!("Game {} is impossible:", game.id);
+        for e in excesses {
+            println!(
+                "  draw {}: {} {}, {} over the limit of {}",
+                e.draw + 1,
+                e.count,
+                e.colour,
+                e.count - e.limit,
+                e.limit
+            );
+        }
+    }
+
+    println!();
+    println!("Part 2:");
+    for game in &games {
+        match crate::power(game, &no_cubes) {
+            Some(power) => println!("Game {}: power {}", game.id, power),
+            None => println!("Game {}: power too big for 64 bits", game.id),
+        }
+        for (col, num, draw) in minimum_sources(game, no_cubes.colours()) {
+            match draw {
+                Some(draw) => println!("  {} {} from draw {}", num, col, draw + 1),
+                None => println!("  0 {} (never drawn)", col),
+            }
+        }
+    }
+}
+
+#[test]
+fn explanations() {
+    let game: Game = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        excesses(&game, &crate::default_bag()),
+        vec![Excess {
+            draw: 0,
+            colour: "red".to_string(),
+            count: 20,
+            limit: 12
+        }]
+    );
+
+    assert_eq!(
+        minimum_sources(&game, ["blue", "green", "red", "yellow"].into_iter()),
+        vec![
+            ("blue", 6, Some(0)),
+            ("green", 13, Some(1)),
+            ("red", 20, Some(0)),
+            ("yellow", 0, None),
+        ]
+    );
+}
@@ -0,0 +1,169 @@
+use std::io::Write;
+
+use crate::{bags, set::Set, Game};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    JsonLines,
+    Csv,
+}
+
+/// Write one record per game: its draws, smallest bag, power, and whether `bag` allows it.
+pub fn export(
+    out: &mut impl Write,
+    games: &[Game],
+    bag: &Set,
+    format: Format,
+) -> std::io::Result<()> {
+    let no_cubes = crate::no_cubes(games);
+    let colours: Vec<&str> = no_cubes.colours().collect();
+
+    if format == Format::Csv {
+        let header: Vec<String> = ["id".to_string(), "draws".to_string()]
+            .into_iter()
+            .chain(colours.iter().map(|col| format!("min_{}", col)))
+            .chain(["power".to_string(), "possible".to_string()])
+            .collect();
+        writeln!(
+            out,
+            "{}",
+            header
+                .iter()
+                .map(|h| csv_field(h))
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+    }
+
+    for game in games {
+        let min_bag = game.min_bag();
+        // A power too big to hold is left out: null in JSON, empty in CSV
+        let power = crate::power(game, &no_cubes);
+        let possible = game.draws.iter().all(|draw| draw <= bag);
+
+        match format {
+            Format::JsonLines => {
+                let draws: Vec<String> = game.draws.iter().map(json_set).collect();
+                writeln!(
+                    out,
+                    "{{\"id\":{},\"draws\":[{}],\"min_bag\":{},\"power\":{},\"possible\":{}}}",
+                    game.id,
+                    draws.join(","),
+                    json_set(&min_bag),
+                    power.map_or("null".to_string(), |power| power.to_string()),
+                    possible
+                )?;
+            }
+            Format::Csv => {
+                let draws: Vec<String> = game.draws.iter().map(Set::to_string).collect();
+                let fields: Vec<String> = [game.id.to_string(), csv_field(&draws.join("; "))]
+                    .into_iter()
+                    .chain(colours.iter().map(|&col| min_bag[col].to_string()))
+                    .chain([
+                        power.map_or(String::new(), |power| power.to_string()),
+                        possible.to_string(),
+                    ])
+                    .collect();
+                writeln!(out, "{}", fields.join(","))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn json_set(set: &Set) -> String {
+    let fields: Vec<String> = set
+        .iter()
+        .map(|(col, num)| format!("{}:{}", json_string(col), num))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// `export [--format json|csv] [--bag <spec>] [--out <path>]`
+pub fn run(games: &[Game], args: &[String]) {
+    let mut format = Format::JsonLines;
+    let mut bag = crate::default_bag();
+    let mut path = None;
+
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, f] if flag == "--format" && f == "json" => format = Format::JsonLines,
+            [flag, f] if flag == "--format" && f == "csv" => format = Format::Csv,
+            [flag, spec] if flag == "--bag" => match bags::parse_bag(spec) {
+                Ok(set) => bag = set,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            },
+            [flag, p] if flag == "--out" => path = Some(p),
+            _ => {
+                eprintln!("Bad argument {:?}", pair.join(" "));
+                return;
+            }
+        }
+    }
+
+    let result = match path {
+        Some(path) => std::fs::File::create(path).and_then(|f| {
+            let mut out = std::io::BufWriter::new(f);
+            export(&mut out, games, &bag, format)?;
+            out.flush()
+        }),
+        None => export(&mut std::io::stdout().lock(), games, &bag, format),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Couldn't write export: {}", e);
+    }
+}
+
+#[test]
+fn formats() {
+    let games = crate::parse_input(
+        "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 3: 20 red; 1 blue",
+    )
+    .unwrap();
+    let write = |format| {
+        let mut out = Vec::new();
+        export(&mut out, &games, &crate::default_bag(), format).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!(
+        write(Format::JsonLines),
+        "{\"id\":1,\"draws\":[{\"blue\":3,\"red\":4},{\"blue\":6,\"green\":2,\"red\":1},{\"green\":2}],\
+         \"min_bag\":{\"blue\":6,\"green\":2,\"red\":4},\"power\":48,\"possible\":true}\n\
+         {\"id\":3,\"draws\":[{\"red\":20},{\"blue\":1}],\
+         \"min_bag\":{\"blue\":1,\"red\":20},\"power\":0,\"possible\":false}\n"
+    );
+    assert_eq!(
+        write(Format::Csv),
+        "id,draws,min_blue,min_green,min_red,power,possible\n\
+         1,\"3 blue, 4 red; 6 blue, 2 green, 1 red; 2 green\",6,2,4,48,true\n\
+         3,20 red; 1 blue,1,0,20,0,false\n"
+    );
+}
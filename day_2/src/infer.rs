@@ -0,0 +1,256 @@
+//! Estimating what was in the bag from the draws seen in a game.
+//!
+//! Each draw is taken to be a handful pulled out without replacement, and the
+//! cubes go back in the bag before the next draw. So the chance of one draw is
+//! multivariate hypergeometric:
+//!
+//! ```text
+//! P(draw | bag) = Π C(bag[c], draw[c]) / C(total(bag), total(draw))
+//! ```
+//!
+//! With a uniform prior over every bag up to `cap` cubes of each colour, the
+//! posterior is just the normalised likelihood, so the most likely bag is both
+//! the maximum-likelihood and the maximum-a-posteriori estimate.
+
+use crate::{set::Set, Game};
+
+/// Don't search more bags than this for a single game. Nothing is kept per
+/// bag, so this only bounds the time taken.
+const MAX_BAGS: u64 = 50_000_000;
+
+/// Don't consider bags holding more cubes than this, as there's a table of
+/// log-factorials as long as the biggest bag.
+const MAX_CUBES: u64 = 1_000_000;
+
+/// What one colour's posterior looks like.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub colour: String,
+    pub mean: f64,
+    pub low: u32,
+    pub high: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    pub most_likely: Set,
+    /// Central credible interval for each colour, at the level asked for.
+    pub intervals: Vec<Interval>,
+}
+
+/// Estimate the bag behind `game`, considering counts of up to `cap` for each of `colours`.
+///
+/// `level` is the probability mass of the credible intervals, e.g. 0.9.
+pub fn estimate(game: &Game, colours: &[&str], cap: u32, level: f64) -> Result<Estimate, String> {
+    let seen = game.min_bag();
+    let low: Vec<u32> = colours.iter().map(|&col| seen[col]).collect();
+    let high: Vec<u32> = low.iter().map(|&lo| lo.max(cap)).collect();
+
+    low.iter()
+        .zip(&high)
+        .try_fold(1u64, |n, (lo, hi)| n.checked_mul((hi - lo + 1) as u64))
+        .filter(|&n| n <= MAX_BAGS)
+        .ok_or_else(|| format!("Game {}: too many bags to search, try a lower cap", game.id))?;
+    let most_cubes: u64 = high.iter().map(|&hi| u64::from(hi)).sum();
+    if most_cubes > MAX_CUBES {
+        return Err(format!(
+            "Game {}: bags of up to {} cubes are too big to search, try a lower cap",
+            game.id, most_cubes
+        ));
+    }
+
+    let draws: Vec<Vec<u32>> = game
+        .draws
+        .iter()
+        .map(|draw| colours.iter().map(|&col| draw[col]).collect())
+        .collect();
+    let ln_fact = ln_factorials(most_cubes as usize);
+    let ln_choose =
+        |n: u64, k: u64| ln_fact[n as usize] - ln_fact[k as usize] - ln_fact[(n - k) as usize];
+
+    // Marginal posterior of each colour, built in one pass over the grid. The
+    // weights are kept relative to the best log-likelihood so far, and scaled
+    // down whenever a better one turns up, so they never underflow.
+    let mut marginals: Vec<Vec<f64>> = low
+        .iter()
+        .zip(&high)
+        .map(|(lo, hi)| vec![0.0; (hi - lo + 1) as usize])
+        .collect();
+    let mut norm = 0.0;
+    let mut best_ll = f64::NEG_INFINITY;
+    let mut best = None;
+    let mut bag = low.clone();
+    loop {
+        let total: u64 = bag.iter().map(|&n| u64::from(n)).sum();
+        let ll: f64 = draws
+            .iter()
+            .map(|draw| {
+                let drawn: u64 = draw.iter().map(|&k| u64::from(k)).sum();
+                bag.iter()
+                    .zip(draw)
+                    .map(|(&n, &k)| ln_choose(n.into(), k.into()))
+                    .sum::<f64>()
+                    - ln_choose(total, drawn)
+            })
+            .sum();
+
+        if ll > best_ll {
+            let scale = (best_ll - ll).exp();
+            norm *= scale;
+            marginals.iter_mut().flatten().for_each(|p| *p *= scale);
+            best_ll = ll;
+            best = Some(bag.clone());
+        }
+        let w = (ll - best_ll).exp();
+        norm += w;
+        for (c, &n) in bag.iter().enumerate() {
+            marginals[c][(n - low[c]) as usize] += w;
+        }
+
+        if !advance(&mut bag, &low, &high) {
+            break;
+        }
+    }
+    marginals.iter_mut().flatten().for_each(|p| *p /= norm);
+
+    let tail = (1.0 - level) / 2.0;
+    let intervals = colours
+        .iter()
+        .zip(&marginals)
+        .zip(&low)
+        .map(|((col, marginal), &lo)| {
+            let mut cumulative = 0.0;
+            let mut low_n = None;
+            let mut high_n = lo + marginal.len() as u32 - 1;
+            for (i, p) in marginal.iter().enumerate() {
+                cumulative += p;
+                if low_n.is_none() && cumulative > tail {
+                    low_n = Some(lo + i as u32);
+                }
+                if cumulative >= 1.0 - tail {
+                    high_n = lo + i as u32;
+                    break;
+                }
+            }
+
+            Interval {
+                colour: col.to_string(),
+                mean: marginal
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| (lo + i as u32) as f64 * p)
+                    .sum(),
+                low: low_n.unwrap_or(lo),
+                high: high_n,
+            }
+        })
+        .collect();
+
+    Ok(Estimate {
+        most_likely: colours.iter().copied().zip(best.unwrap()).collect(),
+        intervals,
+    })
+}
+
+/// Step `bag` to the next point in the grid, returning false once it wraps around.
+fn advance(bag: &mut [u32], low: &[u32], high: &[u32]) -> bool {
+    for c in (0..bag.len()).rev() {
+        if bag[c] < high[c] {
+            bag[c] += 1;
+            return true;
+        }
+        bag[c] = low[c];
+    }
+    false
+}
+
+fn ln_factorials(n: usize) -> Vec<f64> {
+    let mut table = vec![0.0; n + 1];
+    for i in 1..=n {
+        table[i] = table[i - 1] + (i as f64).ln();
+    }
+    table
+}
+
+/// Print an estimate for every game, or just `--game <id>`.
+pub fn run(games: &[Game], args: &[String]) {
+    let mut cap = 30;
+    let mut level = 0.9;
+    let mut only: Option<u32> = None;
+
+    for pair in args.chunks(2) {
+        let parsed = match pair {
+            [flag, n] if flag == "--cap" => n.parse().map(|n| cap = n).is_ok(),
+            [flag, p] if flag == "--level" => p.parse().map(|p| level = p).is_ok(),
+            [flag, id] if flag == "--game" => id.parse().map(|id| only = Some(id)).is_ok(),
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("Bad argument {:?}", pair.join(" "));
+            return;
+        }
+    }
+
+    let no_cubes = crate::no_cubes(games);
+    let colours: Vec<&str> = no_cubes.colours().collect();
+
+    for game in games
+        .iter()
+        .filter(|game| only.is_none_or(|id| id == game.id))
+    {
+        match estimate(game, &colours, cap, level) {
+            Ok(estimate) => {
+                println!("Game {}: most likely {}", game.id, estimate.most_likely);
+                for i in estimate.intervals {
+                    println!(
+                        "  {}: mean {:.1}, {:.0}% interval {}..={}",
+                        i.colour,
+                        i.mean,
+                        level * 100.0,
+                        i.low,
+                        i.high
+                    );
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+#[test]
+fn single_draw() {
+    let game: Game = "Game 1: 2 red".parse().unwrap();
+    let estimate = estimate(&game, &["blue", "red"], 3, 0.9).unwrap();
+
+    // Any blue cube would have made two reds less likely, and 2 or 3 reds are equally likely
+    assert_eq!(estimate.most_likely, Set::from([("red", 2)]));
+    assert_eq!(estimate.intervals[0].low, 0);
+    assert!(estimate.intervals[0].mean < 1.0);
+    assert_eq!(estimate.intervals[1].low, 2);
+    assert_eq!(estimate.intervals[1].high, 3);
+}
+
+#[test]
+fn too_many_cubes() {
+    for line in [
+        "Game 1: 2000000000 red",
+        "Game 2: 4000000000 red, 4000000000 blue",
+    ] {
+        let game: Game = line.parse().unwrap();
+        let e = estimate(&game, &["blue", "red"], 3, 0.9).unwrap_err();
+        assert!(e.ends_with("try a lower cap"), "{}", e);
+    }
+}
+
+#[test]
+fn never_below_what_was_seen() {
+    let games = crate::parse_input(crate::INPUT_SAMPLE).unwrap();
+
+    for game in &games {
+        let estimate = estimate(game, &["blue", "green", "red"], 25, 0.9).unwrap();
+        assert!(game.min_bag() <= estimate.most_likely);
+        for i in &estimate.intervals {
+            assert!(i.low <= i.high && game.min_bag()[&i.colour] <= i.low);
+        }
+    }
+}
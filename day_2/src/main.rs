@@ -1,122 +1,280 @@
-use std::ops::{Index, IndexMut};
+mod bags;
+mod explain;
+mod export;
+mod infer;
+mod set;
+mod simulate;
+mod validate;
+
+use std::{fmt::Display, str::FromStr};
+
+use set::Set;
 
 const INPUT: &str = include_str!("input.txt");
+#[cfg(test)]
+const INPUT_SAMPLE: &str = include_str!("input_sample.txt");
 
-#[derive(Default)]
-struct Set {
-    red: u32,
-    green: u32,
-    blue: u32,
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Game {
+    id: u32,
+    draws: Vec<Set>,
 }
 
-impl Index<&str> for Set {
-    type Output = u32;
-
-    fn index(&self, index: &str) -> &Self::Output {
-        match index {
-            "red" => &self.red,
-            "green" => &self.green,
-            "blue" => &self.blue,
-            _ => panic!("Index {} not valid for colour set.", index),
-        }
+impl Game {
+    /// The fewest cubes of each colour that could have produced every draw.
+    fn min_bag(&self) -> Set {
+        Set::union_all(&self.draws)
     }
 }
 
-impl IndexMut<&str> for Set {
-    fn index_mut(&mut self, index: &str) -> &mut Self::Output {
-        match index {
-            "red" => &mut self.red,
-            "green" => &mut self.green,
-            "blue" => &mut self.blue,
-            _ => panic!("Index {} not valid for colour set.", index),
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParseError {
+    /// The part before the `:` wasn't `Game <id>`.
+    Header(String),
+    /// A `<count> <colour>` group in game `game` couldn't be read.
+    Group { game: u32, group: String },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Header(line) => write!(f, "Couldn't find a game id in {:?}", line),
+            ParseError::Group { game, group } => {
+                write!(f, "Game {}: couldn't read group {:?}", game, group)
+            }
         }
     }
 }
 
-fn main() {
-    part_1();
-    part_2();
-}
+impl FromStr for Game {
+    type Err = ParseError;
 
-fn part_1() {
-    let allowed = Set {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, draws) = split_header(s)?;
 
-    let mut sum_of_ids = 0;
-
-    for l in INPUT.lines() {
-        let game_id: u32 = l
-            .split(':')
-            .next()
-            .unwrap()
-            .split(" ")
-            .nth(1)
-            .unwrap()
-            .parse()
-            .unwrap();
-
-        if l.split(':')
-            .nth(1)
-            .unwrap()
+        let draws = draws
             .split(';')
-            .map(|set| {
-                set.split(',').fold(Set::default(), |mut cum_set, group| {
-                    let mut iter = group.trim().split(' ');
-                    let num: u32 = iter.next().unwrap().parse().unwrap();
-                    let col = iter.next().unwrap();
-
-                    cum_set[col] += num;
-                    cum_set
+            .map(|draw| {
+                parse_draw(draw).map_err(|group| ParseError::Group {
+                    game: id,
+                    group: group.trim().to_string(),
                 })
             })
-            .all(|set| {
-                set["red"] <= allowed["red"]
-                    && set["green"] <= allowed["green"]
-                    && set["blue"] <= allowed["blue"]
-            })
-        {
-            sum_of_ids += game_id;
+            .collect::<Result<_, _>>()?;
+
+        Ok(Game { id, draws })
+    }
+}
+
+/// Split `Game <id>: <draws>` into the id and the draws.
+fn split_header(s: &str) -> Result<(u32, &str), ParseError> {
+    let header_err = || ParseError::Header(s.to_string());
+
+    let (header, draws) = s.split_once(':').ok_or_else(header_err)?;
+    let id = header
+        .strip_prefix("Game ")
+        .and_then(|id| id.trim().parse().ok())
+        .ok_or_else(header_err)?;
+
+    Ok((id, draws))
+}
+
+/// Read a single `<count> <colour>` group.
+fn parse_group(group: &str) -> Option<(u32, &str)> {
+    let mut iter = group.split_whitespace();
+    let num = iter.next()?.parse().ok()?;
+    let col = iter.next()?;
+
+    iter.next().is_none().then_some((num, col))
+}
+
+/// Read every group in one draw, returning the offending group if there is one.
+///
+/// A blank draw has no groups.
+fn parse_groups(draw: &str) -> Result<Vec<(u32, &str)>, &str> {
+    if draw.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    draw.split(',')
+        .map(|group| parse_group(group).ok_or(group))
+        .collect()
+}
+
+/// Read one draw. Counts for a colour that's repeated are added together.
+fn parse_draw(draw: &str) -> Result<Set, &str> {
+    Ok(parse_groups(draw)?
+        .into_iter()
+        .map(|(num, col)| (col, num))
+        .collect())
+}
+
+fn parse_input(input: &str) -> Result<Vec<Game>, ParseError> {
+    input
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Anomalies are only warned about unless --strict is given
+    let strict = args.iter().any(|a| a == "--strict");
+    args.retain(|a| a != "--strict");
+
+    // The games come from the puzzle input unless --input names a file
+    let input = match args.iter().position(|a| a == "--input") {
+        Some(i) if i + 1 < args.len() => {
+            let path = args.drain(i..i + 2).nth(1).unwrap();
+            match std::fs::read_to_string(&path) {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("Couldn't read {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            }
         }
+        Some(_) => {
+            eprintln!("--input needs a path");
+            std::process::exit(1);
+        }
+        None => INPUT.to_string(),
+    };
+
+    let games = match parse_input(&input) {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    let anomalies = validate::validate(&input).unwrap_or_default();
+    if args.first().map(String::as_str) == Some("validate") {
+        validate::report(&anomalies);
+        if strict && !anomalies.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+    for anomaly in &anomalies {
+        eprintln!("{}: {}", if strict { "Error" } else { "Warning" }, anomaly);
+    }
+    if strict && !anomalies.is_empty() {
+        std::process::exit(1);
     }
 
-    println!("Sum of game ids: {}", sum_of_ids);
+    match args.first().map(String::as_str) {
+        None => {
+            part_1(&games);
+            part_2(&games);
+        }
+        Some("explain") => explain::run(&games, &args[1..]),
+        Some("export") => export::run(&games, &args[1..]),
+        Some("infer") => infer::run(&games, &args[1..]),
+        Some("simulate") => simulate::run(&args[1..]),
+        Some(_) => match bags::from_args(&args) {
+            Ok(bags) => bags::run(&games, &bags),
+            Err(e) => eprintln!("{}", e),
+        },
+    }
 }
 
-fn part_2() {
-    let mut sum_of_powers: u32 = 0;
+fn sum_of_possible_ids(games: &[Game], allowed: &Set) -> u64 {
+    bags::possible_games(games, allowed).map(u64::from).sum()
+}
 
-    for l in INPUT.lines() {
-        let mut min_req = Set {
-            red: 0,
-            green: 0,
-            blue: 0,
-        };
+/// Every colour that shows up in any game, with no cubes of each.
+fn no_cubes(games: &[Game]) -> Set {
+    games
+        .iter()
+        .flat_map(|game| game.draws.iter().flat_map(Set::colours))
+        .map(|col| (col, 0))
+        .collect()
+}
 
-        l.split(':')
-            .nth(1)
-            .unwrap()
-            .split(';')
-            .map(|set| {
-                set.split(',').fold(Set::default(), |mut cum_set, group| {
-                    let mut iter = group.trim().split(' ');
-                    let num: u32 = iter.next().unwrap().parse().unwrap();
-                    let col = iter.next().unwrap();
-
-                    cum_set[col] += num;
-                    cum_set
-                })
-            })
-            .for_each(|set| {
-                min_req["red"] = min_req["red"].max(set["red"]);
-                min_req["green"] = min_req["green"].max(set["green"]);
-                min_req["blue"] = min_req["blue"].max(set["blue"]);
-            });
+/// The power of the smallest bag for `game`, over every colour in `no_cubes`. Colours seen
+/// elsewhere but not in this game count as zero, so the power is zero too.
+fn power(game: &Game, no_cubes: &Set) -> Option<u64> {
+    game.min_bag().power(no_cubes.colours())
+}
+
+fn sum_of_powers(games: &[Game]) -> Result<u64, String> {
+    let no_cubes = no_cubes(games);
+    games.iter().try_fold(0u64, |sum, game| {
+        power(game, &no_cubes)
+            .and_then(|power| sum.checked_add(power))
+            .ok_or_else(|| format!("the power of game {} overflows", game.id))
+    })
+}
+
+fn default_bag() -> Set {
+    Set::from([("red", 12), ("green", 13), ("blue", 14)])
+}
+
+fn part_1(games: &[Game]) {
+    println!(
+        "Sum of game ids: {}",
+        sum_of_possible_ids(games, &default_bag())
+    );
+}
 
-        sum_of_powers += min_req["red"] * min_req["green"] * min_req["blue"];
+fn part_2(games: &[Game]) {
+    match sum_of_powers(games) {
+        Ok(sum) => println!("Sum of minimum powers: {}", sum),
+        Err(e) => eprintln!("Part 2 failed: {}", e),
     }
+}
+
+#[test]
+fn sample() {
+    let games = parse_input(INPUT_SAMPLE).unwrap();
+
+    assert_eq!(sum_of_possible_ids(&games, &default_bag()), 8);
+    assert_eq!(sum_of_powers(&games), Ok(2286));
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        "Game 3: 1 red; 2 green, blue".parse::<Game>(),
+        Err(ParseError::Group {
+            game: 3,
+            group: "blue".to_string()
+        })
+    );
+    assert_eq!(
+        "Game x: 1 red".parse::<Game>(),
+        Err(ParseError::Header("Game x: 1 red".to_string()))
+    );
+}
+
+#[test]
+fn other_colours() {
+    let games = parse_input(
+        "Game 1: 2 yellow, 1 red; 3 purple\nGame 2: 5 yellow\nGame 3: 1 purple, 1 purple",
+    )
+    .unwrap();
+    let bag = Set::from([("yellow", 4), ("purple", 3), ("red", 1)]);
+
+    assert_eq!(games[2].draws[0], Set::from([("purple", 2)]));
+    assert_eq!(sum_of_possible_ids(&games, &bag), 1 + 3);
+    // Games 2 and 3 never show every colour, so only game 1 has any power
+    assert_eq!(sum_of_powers(&games), Ok(2 * 3));
+
+    // A game of nothing but blank draws has no cubes of any colour
+    let games = parse_input("Game 1: 2 red\nGame 2: ;\n").unwrap();
+    assert_eq!(power(&games[1], &no_cubes(&games)), Some(0));
 
-    println!("Sum of minimum powers: {}", sum_of_powers);
+    let games = parse_input(&format!(
+        "Game 1: 1 red\nGame 2: {0} red, {0} green, {0} blue\n",
+        u32::MAX
+    ))
+    .unwrap();
+    assert_eq!(
+        sum_of_powers(&games),
+        Err("the power of game 2 overflows".to_string())
+    );
 }
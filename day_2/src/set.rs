@@ -0,0 +1,233 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, Index, IndexMut, Sub},
+};
+
+/// Cube counts by colour. A colour that isn't in the set counts as zero.
+///
+/// Sets are partially ordered by "fits inside": `a <= b` when every colour in
+/// `a` has no more cubes than in `b`.
+#[derive(Debug, Default, Clone)]
+pub struct Set(BTreeMap<String, u32>);
+
+impl Set {
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.0.iter().map(|(col, &num)| (col.as_str(), num))
+    }
+
+    pub fn colours(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Total number of cubes.
+    pub fn total(&self) -> u64 {
+        self.0.values().map(|&num| u64::from(num)).sum()
+    }
+
+    /// The product of the counts of each of `colours`, or `None` if it's too big for a `u64`.
+    ///
+    /// The colours have to be given, since one that's missing counts as zero
+    /// just as much as one that's there with no cubes.
+    pub fn power<'a>(&self, colours: impl IntoIterator<Item = &'a str>) -> Option<u64> {
+        colours.into_iter().try_fold(1u64, |product, col| {
+            product.checked_mul(u64::from(self[col]))
+        })
+    }
+
+    /// Element-wise max: the smallest set that both `self` and `other` fit inside.
+    pub fn union(&self, other: &Set) -> Set {
+        self.zip_with(other, u32::max)
+    }
+
+    /// Element-wise min: the largest set that fits inside both `self` and `other`.
+    pub fn intersection(&self, other: &Set) -> Set {
+        self.zip_with(other, u32::min)
+    }
+
+    /// The smallest set that every one of `sets` fits inside.
+    pub fn union_all<'a>(sets: impl IntoIterator<Item = &'a Set>) -> Set {
+        sets.into_iter()
+            .fold(Set::default(), |acc, set| acc.union(set))
+    }
+
+    fn zip_with(&self, other: &Set, f: impl Fn(u32, u32) -> u32) -> Set {
+        self.colours()
+            .chain(other.colours())
+            .map(|col| (col, f(self[col], other[col])))
+            .fold(Set::default(), |mut set, (col, num)| {
+                set[col] = num;
+                set
+            })
+    }
+}
+
+impl PartialEq for Set {
+    fn eq(&self, other: &Self) -> bool {
+        self.colours()
+            .chain(other.colours())
+            .all(|col| self[col] == other[col])
+    }
+}
+
+impl Eq for Set {}
+
+impl PartialOrd for Set {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let fits = |a: &Set, b: &Set| a.iter().all(|(col, num)| num <= b[col]);
+
+        match (fits(self, other), fits(other, self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Colours that would go past `u32::MAX` are left at `u32::MAX`.
+impl Add<&Set> for &Set {
+    type Output = Set;
+
+    fn add(self, rhs: &Set) -> Self::Output {
+        self.zip_with(rhs, u32::saturating_add)
+    }
+}
+
+impl Add for Set {
+    type Output = Set;
+
+    fn add(self, rhs: Set) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+/// Colours that would go negative are left at zero.
+impl Sub<&Set> for &Set {
+    type Output = Set;
+
+    fn sub(self, rhs: &Set) -> Self::Output {
+        self.zip_with(rhs, u32::saturating_sub)
+    }
+}
+
+impl Sub for Set {
+    type Output = Set;
+
+    fn sub(self, rhs: Set) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl<'a> Sum<&'a Set> for Set {
+    fn sum<I: Iterator<Item = &'a Set>>(iter: I) -> Self {
+        iter.fold(Set::default(), |acc, set| &acc + set)
+    }
+}
+
+impl Sum for Set {
+    fn sum<I: Iterator<Item = Set>>(iter: I) -> Self {
+        iter.fold(Set::default(), |acc, set| acc + set)
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, u32)> for Set {
+    fn from_iter<T: IntoIterator<Item = (S, u32)>>(iter: T) -> Self {
+        let mut set = Set::default();
+        iter.into_iter().for_each(|(col, num)| {
+            let count = set.0.entry(col.into()).or_default();
+            *count = count.saturating_add(num);
+        });
+        set
+    }
+}
+
+impl<S: Into<String>, const N: usize> From<[(S, u32); N]> for Set {
+    fn from(value: [(S, u32); N]) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// Written the way draws are in the input, e.g. `3 blue, 4 red`.
+impl Display for Set {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (col, num)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", num, col)?;
+        }
+        Ok(())
+    }
+}
+
+impl Index<&str> for Set {
+    type Output = u32;
+
+    fn index(&self, index: &str) -> &Self::Output {
+        self.0.get(index).unwrap_or(&0)
+    }
+}
+
+impl IndexMut<&str> for Set {
+    fn index_mut(&mut self, index: &str) -> &mut Self::Output {
+        if !self.0.contains_key(index) {
+            self.0.insert(index.to_string(), 0);
+        }
+        self.0.get_mut(index).unwrap()
+    }
+}
+
+#[test]
+fn algebra() {
+    let a = Set::from([("red", 3), ("blue", 1)]);
+    let b = Set::from([("red", 1), ("green", 2)]);
+
+    assert_eq!(
+        a.union(&b),
+        Set::from([("red", 3), ("green", 2), ("blue", 1)])
+    );
+    assert_eq!(a.intersection(&b), Set::from([("red", 1)]));
+    assert_eq!(&a + &b, Set::from([("red", 4), ("green", 2), ("blue", 1)]));
+    assert_eq!(&a - &b, Set::from([("red", 2), ("blue", 1)]));
+    assert_eq!([a.clone(), b.clone()].iter().sum::<Set>(), &a + &b);
+    assert_eq!(Set::union_all([&a, &b]), a.union(&b));
+    assert_eq!(
+        Set::from([("red", 2), ("green", 3), ("blue", 4)]).power(["red", "green", "blue"]),
+        Some(24)
+    );
+    // Equal sets have equal powers, whether or not their zeros are written down
+    assert_eq!(Set::from([("red", 0)]), Set::default());
+    assert_eq!(Set::from([("red", 0)]).power(["red"]), Some(0));
+    assert_eq!(Set::default().power(["red"]), Some(0));
+    assert_eq!((&a - &a).power(["red", "blue"]), Some(0));
+
+    // Five colours of 100 is already past a u32
+    let big: Set = ["red", "green", "blue", "yellow", "purple"]
+        .map(|col| (col, 100))
+        .into();
+    assert_eq!(big.power(big.colours()), Some(10_000_000_000));
+    assert_eq!(big.total(), 500);
+    let huge: Set = (0..8).map(|i| (i.to_string(), u32::MAX)).collect();
+    assert_eq!(huge.power(huge.colours()), None);
+    assert_eq!(huge.total(), 8 * u64::from(u32::MAX));
+    assert_eq!((&huge + &huge)["0"], u32::MAX);
+}
+
+#[test]
+fn fits_inside() {
+    let bag = Set::from([("red", 12), ("green", 13), ("blue", 14)]);
+
+    assert!(Set::from([("red", 12)]) <= bag);
+    assert!(Set::from([("red", 12), ("green", 0)]) < bag);
+    assert!(bag >= bag.clone());
+    assert!(Set::from([("red", 13)]) > Set::from([("red", 12)]));
+    assert_eq!(Set::from([("yellow", 1)]).partial_cmp(&bag), None);
+    assert_eq!(
+        Set::from([("red", 13)]).partial_cmp(&Set::from([("blue", 1)])),
+        None
+    );
+    assert_eq!(Set::from([("red", 0)]), Set::default());
+}
@@ -0,0 +1,196 @@
+use std::io::Write;
+
+use crate::{bags, set::Set};
+
+/// Small splitmix64 generator, so a seed always gives the same games.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Pull a handful of between 1 and all of the cubes out of `bag`, without replacement.
+///
+/// Colours are listed in the order they first came out of the bag. Each cube
+/// is picked by counting through what's left of each colour, so there's no
+/// need to lay every cube out at once.
+pub fn draw<'a>(bag: &'a Set, rng: &mut Rng) -> Vec<(&'a str, u32)> {
+    let mut left: Vec<(&str, u32)> = bag.iter().filter(|&(_, num)| num > 0).collect();
+    let total = bag.total() as usize;
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let size = 1 + rng.below(total);
+    let mut handful: Vec<(&str, u32)> = Vec::new();
+    let mut taken = 0;
+    while taken < size {
+        // Once only one colour is left, the rest of the handful is all that colour
+        let (col, count) = if let [(col, _)] = left[..] {
+            (col, size - taken)
+        } else {
+            let mut i = rng.below(total - taken);
+            let c = left
+                .iter()
+                .position(|&(_, num)| match i.checked_sub(num as usize) {
+                    Some(rest) => {
+                        i = rest;
+                        false
+                    }
+                    None => true,
+                })
+                .unwrap();
+            left[c].1 -= 1;
+            let col = left[c].0;
+            left.retain(|&(_, num)| num > 0);
+            (col, 1)
+        };
+        taken += count;
+
+        match handful.iter_mut().find(|(c, _)| *c == col) {
+            Some((_, num)) => *num += count as u32,
+            None => handful.push((col, count as u32)),
+        }
+    }
+
+    handful
+}
+
+/// Write `games` games of `draws` draws each from `bag`, in the puzzle's input format.
+pub fn simulate(
+    out: &mut impl Write,
+    bag: &Set,
+    games: u32,
+    draws: u32,
+    rng: &mut Rng,
+) -> std::io::Result<()> {
+    for id in 1..=games {
+        let draws: Vec<String> = (0..draws)
+            .map(|_| {
+                draw(bag, rng)
+                    .iter()
+                    .map(|(col, num)| format!("{} {}", num, col))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+
+        writeln!(out, "Game {}: {}", id, draws.join("; "))?;
+    }
+
+    Ok(())
+}
+
+/// `simulate [--bag <bag>] [--games <n>] [--draws <n>] [--seed <n>] [--out <path>]`
+///
+/// A file written with `--out` can be solved like the puzzle input with `--input <path>`.
+pub fn run(args: &[String]) {
+    let mut bag = crate::default_bag();
+    let mut games = 100;
+    let mut draws = 6;
+    let mut seed = 0;
+    let mut path = None;
+
+    for pair in args.chunks(2) {
+        let parsed = match pair {
+            [flag, spec] if flag == "--bag" => match bags::parse_bag(spec) {
+                Ok(set) => {
+                    bag = set;
+                    true
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            },
+            [flag, n] if flag == "--games" => n.parse().map(|n| games = n).is_ok(),
+            [flag, n] if flag == "--draws" => n.parse().map(|n| draws = n).is_ok(),
+            [flag, s] if flag == "--seed" => s.parse().map(|s| seed = s).is_ok(),
+            [flag, p] if flag == "--out" => {
+                path = Some(p);
+                true
+            }
+            _ => false,
+        };
+        if !parsed {
+            eprintln!("Bad argument {:?}", pair.join(" "));
+            return;
+        }
+    }
+
+    if bag.total() == 0 {
+        eprintln!("The bag needs at least one cube");
+        return;
+    }
+
+    let mut rng = Rng::new(seed);
+    let result = match path {
+        Some(path) => std::fs::File::create(path).and_then(|f| {
+            let mut out = std::io::BufWriter::new(f);
+            simulate(&mut out, &bag, games, draws, &mut rng)?;
+            out.flush()
+        }),
+        None => simulate(&mut std::io::stdout().lock(), &bag, games, draws, &mut rng),
+    };
+
+    match (result, path) {
+        (Err(e), _) => eprintln!("Couldn't write games: {}", e),
+        (Ok(()), Some(path)) => eprintln!(
+            "Wrote {} games to {1}, solve them with --input {1}",
+            games, path
+        ),
+        (Ok(()), None) => {}
+    }
+}
+
+#[test]
+fn simulated_games_round_trip() {
+    let bag = Set::from([("red", 5), ("yellow", 3), ("purple", 1)]);
+    let write = |seed| {
+        let mut out = Vec::new();
+        simulate(&mut out, &bag, 50, 4, &mut Rng::new(seed)).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let text = write(7);
+    assert_eq!(text, write(7));
+    assert_ne!(text, write(8));
+
+    let games = crate::parse_input(&text).unwrap();
+    assert_eq!(games.len(), 50);
+    for game in &games {
+        assert_eq!(game.draws.len(), 4);
+        assert!(game.draws.iter().all(|draw| draw.total() > 0));
+        assert!(game.min_bag() <= bag);
+    }
+
+    // Every game is possible with the bag it came from
+    assert_eq!(crate::sum_of_possible_ids(&games, &bag), (1..=50).sum());
+}
+
+#[test]
+fn huge_bags() {
+    let bag = Set::from([("red", 2_000_000_000)]);
+    let handful = draw(&bag, &mut Rng::new(1));
+    assert!(matches!(handful[..], [("red", 1..=2_000_000_000)]));
+
+    let bag = Set::from([("red", 1_000_000), ("blue", 1)]);
+    for seed in 0..20 {
+        let handful: Set = draw(&bag, &mut Rng::new(seed)).into_iter().collect();
+        assert!(handful <= bag && handful.total() > 0);
+    }
+}
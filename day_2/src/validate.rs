@@ -0,0 +1,183 @@
+use std::fmt::Display;
+
+use crate::{parse_groups, split_header, ParseError};
+
+/// Something in the input that parses fine but probably isn't what was meant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anomaly {
+    /// The same colour is listed twice in one draw. The counts get added together.
+    DuplicateColour {
+        game: u32,
+        draw: usize,
+        colour: String,
+    },
+    ZeroCount {
+        game: u32,
+        draw: usize,
+        colour: String,
+    },
+    EmptyDraw {
+        game: u32,
+        draw: usize,
+    },
+    /// Not a single cube was drawn in the whole game.
+    NoCubes {
+        game: u32,
+    },
+    /// Ids should count up from 1 with no gaps. `expected` is `None` after
+    /// the largest id there can be.
+    UnexpectedId {
+        game: u32,
+        expected: Option<u32>,
+    },
+}
+
+impl Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Anomaly::DuplicateColour { game, draw, colour } => {
+                write!(
+                    f,
+                    "Game {}, draw {}: {} is listed more than once",
+                    game,
+                    draw + 1,
+                    colour
+                )
+            }
+            Anomaly::ZeroCount { game, draw, colour } => {
+                write!(f, "Game {}, draw {}: 0 {} cubes", game, draw + 1, colour)
+            }
+            Anomaly::EmptyDraw { game, draw } => {
+                write!(f, "Game {}, draw {}: nothing drawn", game, draw + 1)
+            }
+            Anomaly::NoCubes { game } => write!(f, "Game {}: no cubes drawn at all", game),
+            Anomaly::UnexpectedId {
+                game,
+                expected: Some(expected),
+            } => write!(f, "Game {}: expected game {} here", game, expected),
+            Anomaly::UnexpectedId {
+                game,
+                expected: None,
+            } => write!(
+                f,
+                "Game {}: expected no more games after game {}",
+                game,
+                u32::MAX
+            ),
+        }
+    }
+}
+
+/// Find every anomaly in `input`, in the order they appear.
+pub fn validate(input: &str) -> Result<Vec<Anomaly>, ParseError> {
+    let mut anomalies = Vec::new();
+    let mut expected = Some(1);
+
+    for line in input.lines().filter(|l| !l.trim().is_empty()) {
+        let (game, draws) = split_header(line)?;
+
+        if Some(game) != expected {
+            anomalies.push(Anomaly::UnexpectedId { game, expected });
+        }
+        expected = game.checked_add(1);
+
+        let mut any_cubes = false;
+        for (draw, groups) in draws.split(';').enumerate() {
+            let groups = parse_groups(groups).map_err(|group| ParseError::Group {
+                game,
+                group: group.trim().to_string(),
+            })?;
+
+            if groups.is_empty() {
+                anomalies.push(Anomaly::EmptyDraw { game, draw });
+            }
+
+            for (i, &(num, col)) in groups.iter().enumerate() {
+                any_cubes |= num > 0;
+                let colour = col.to_string();
+
+                if num == 0 {
+                    anomalies.push(Anomaly::ZeroCount {
+                        game,
+                        draw,
+                        colour: colour.clone(),
+                    });
+                }
+                // Only report a repeat the first time it's seen
+                let repeats = groups[..i].iter().filter(|(_, c)| *c == col).count();
+                if repeats == 1 {
+                    anomalies.push(Anomaly::DuplicateColour { game, draw, colour });
+                }
+            }
+        }
+
+        if !any_cubes {
+            anomalies.push(Anomaly::NoCubes { game });
+        }
+    }
+
+    Ok(anomalies)
+}
+
+pub fn report(anomalies: &[Anomaly]) {
+    for anomaly in anomalies {
+        println!("{}", anomaly);
+    }
+    println!("{} anomalies found", anomalies.len());
+}
+
+#[test]
+fn finds_anomalies() {
+    let input = "Game 1: 3 blue, 4 red, 1 blue, 2 blue\n\
+                 Game 2: 0 green; ; 1 red\n\
+                 Game 4: 1 red\n\
+                 Game 5: 0 red";
+
+    assert_eq!(
+        validate(input),
+        Ok(vec![
+            Anomaly::DuplicateColour {
+                game: 1,
+                draw: 0,
+                colour: "blue".to_string()
+            },
+            Anomaly::ZeroCount {
+                game: 2,
+                draw: 0,
+                colour: "green".to_string()
+            },
+            Anomaly::EmptyDraw { game: 2, draw: 1 },
+            Anomaly::UnexpectedId {
+                game: 4,
+                expected: Some(3)
+            },
+            Anomaly::ZeroCount {
+                game: 5,
+                draw: 0,
+                colour: "red".to_string()
+            },
+            Anomaly::NoCubes { game: 5 },
+        ])
+    );
+
+    assert_eq!(validate(crate::INPUT), Ok(Vec::new()));
+
+    // Counts and ids right at the limit are fine, but nothing can come after the last id
+    let input = format!(
+        "Game 1: {0} red, {0} blue\nGame {0}: 1 red\nGame 2: 1 red",
+        u32::MAX
+    );
+    assert_eq!(
+        validate(&input),
+        Ok(vec![
+            Anomaly::UnexpectedId {
+                game: u32::MAX,
+                expected: Some(2)
+            },
+            Anomaly::UnexpectedId {
+                game: 2,
+                expected: None
+            },
+        ])
+    );
+}
@@ -0,0 +1,162 @@
+use std::{fmt::Display, str::FromStr};
+
+/// How far apart two cells can be along each axis and still touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Straight up, down, left or right, within the radius.
+    Orthogonal,
+    /// Anywhere in the square of the radius around a cell.
+    Chebyshev,
+    /// Anywhere in the diamond of the radius around a cell.
+    Manhattan,
+}
+
+/// Which cells count as touching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adjacency {
+    pub metric: Metric,
+    pub radius: usize,
+    /// Whether the grid's edges join up with the opposite edges.
+    pub wrap: bool,
+}
+
+/// The puzzle's rule: the eight cells around, diagonals included.
+impl Default for Adjacency {
+    fn default() -> Self {
+        Adjacency {
+            metric: Metric::Chebyshev,
+            radius: 1,
+            wrap: false,
+        }
+    }
+}
+
+impl Adjacency {
+    /// Distance between two coordinates `d` cells apart on an axis `size` long.
+    fn axis(&self, d: usize, size: usize) -> usize {
+        if self.wrap {
+            d.min(size - d)
+        } else {
+            d
+        }
+    }
+
+    /// How far sideways a cell `dy` rows away can be and still touch.
+    pub fn reach(&self, dy: usize) -> Option<usize> {
+        if dy > self.radius {
+            return None;
+        }
+        Some(match self.metric {
+            Metric::Orthogonal if dy > 0 => 0,
+            Metric::Orthogonal | Metric::Chebyshev => self.radius,
+            Metric::Manhattan => self.radius - dy,
+        })
+    }
+
+    /// Each row within reach of row `y` in a grid `height` rows tall, with its distance from `y`.
+    pub fn rows(&self, y: usize, height: usize) -> Vec<(usize, usize)> {
+        let mut rows: Vec<(usize, usize)> = if self.wrap {
+            (0..=self.radius.min(height / 2))
+                .flat_map(|dy| [(y + height - dy) % height, (y + dy) % height])
+                .map(|row| (row, self.axis(row.abs_diff(y), height)))
+                .collect()
+        } else {
+            (y.saturating_sub(self.radius)
+                ..y.saturating_add(self.radius).saturating_add(1).min(height))
+                .map(|row| (row, row.abs_diff(y)))
+                .collect()
+        };
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    /// Sideways distance from column `x` to the nearest of the `len` cells from column `start`,
+    /// in a grid `width` columns wide.
+    pub fn columns_apart(&self, start: usize, len: usize, x: usize, width: usize) -> usize {
+        let end = start + len - 1;
+        let (direct, around) = if (start..=end).contains(&x) {
+            return 0;
+        } else if x < start {
+            (start - x, x + width - end)
+        } else {
+            (x - end, start + width - x)
+        };
+
+        if self.wrap {
+            direct.min(around)
+        } else {
+            direct
+        }
+    }
+}
+
+impl FromStr for Adjacency {
+    type Err = String;
+
+    /// `<orthogonal|chebyshev|manhattan>[:<radius>][,wrap]`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, wrap) = match s.strip_suffix(",wrap") {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+        let (metric, radius) = match s.split_once(':') {
+            Some((metric, radius)) => (
+                metric,
+                radius
+                    .parse()
+                    .map_err(|_| format!("Expected a radius, got {:?}", radius))?,
+            ),
+            None => (s, 1),
+        };
+        let metric = match metric {
+            "orthogonal" => Metric::Orthogonal,
+            "chebyshev" => Metric::Chebyshev,
+            "manhattan" => Metric::Manhattan,
+            _ => return Err(format!("Unknown adjacency {:?}", metric)),
+        };
+
+        Ok(Adjacency {
+            metric,
+            radius,
+            wrap,
+        })
+    }
+}
+
+impl Display for Adjacency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let metric = match self.metric {
+            Metric::Orthogonal => "orthogonal",
+            Metric::Chebyshev => "chebyshev",
+            Metric::Manhattan => "manhattan",
+        };
+        write!(f, "{}:{}", metric, self.radius)?;
+        if self.wrap {
+            write!(f, ",wrap")?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn adjacency() {
+    assert_eq!("chebyshev".parse(), Ok(Adjacency::default()));
+    let diamond: Adjacency = "manhattan:2,wrap".parse().unwrap();
+    assert_eq!(diamond.to_string(), "manhattan:2,wrap");
+    assert!("hexagonal".parse::<Adjacency>().is_err());
+
+    assert_eq!(diamond.reach(1), Some(1));
+    assert_eq!(diamond.reach(3), None);
+    assert_eq!(
+        diamond.rows(0, 10),
+        vec![(0, 0), (1, 1), (2, 2), (8, 2), (9, 1)]
+    );
+    assert_eq!(diamond.rows(1, 3), vec![(0, 1), (1, 0), (2, 1)]);
+    assert_eq!(diamond.columns_apart(7, 3, 0, 10), 1);
+    assert_eq!(Adjacency::default().columns_apart(7, 3, 0, 10), 7);
+    assert_eq!(Adjacency::default().rows(0, 10), vec![(0, 0), (1, 1)]);
+
+    let huge: Adjacency = format!("chebyshev:{}", usize::MAX).parse().unwrap();
+    assert_eq!(huge.rows(1, 3), vec![(0, 1), (1, 0), (2, 1)]);
+}
@@ -0,0 +1,202 @@
+//! Timing the number index against the old scan over every symbol/number pair.
+
+use std::time::Instant;
+
+use crate::{generate::Rng, grid::Pos, parse_input, Num, Schematic, Value};
+
+/// How many symbols to look around with the scan on big schematics.
+const SAMPLE: usize = 1000;
+
+/// A `size` by `size` schematic with roughly the density of the real input.
+fn generate(size: usize, seed: u64) -> String {
+    const SYMBOLS: &[u8] = b"*#+$/@%=&-";
+
+    let mut rng = Rng::new(seed);
+    let mut text = Vec::with_capacity(size * (size + 1));
+
+    for _ in 0..size {
+        let mut x = 0;
+        while x < size {
+            match rng.below(20) {
+                0..=2 => {
+                    // Always end a number with a dot, so runs don't join into longer numbers
+                    let len = (1 + rng.below(3)).min(size - x);
+                    text.extend((0..len).map(|_| b'0' + rng.below(10) as u8));
+                    x += len;
+                    if x < size {
+                        text.push(b'.');
+                        x += 1;
+                    }
+                }
+                3 => {
+                    text.push(SYMBOLS[rng.below(SYMBOLS.len())]);
+                    x += 1;
+                }
+                _ => {
+                    text.push(b'.');
+                    x += 1;
+                }
+            }
+        }
+        text.push(b'\n');
+    }
+
+    String::from_utf8(text).unwrap()
+}
+
+fn adjacent(s: (usize, usize), n: &Num) -> bool {
+    s.0 >= n.x.saturating_sub(1)
+        && s.0 <= n.x + n.len
+        && s.1 >= n.y.saturating_sub(1)
+        && s.1 <= n.y + 1
+}
+
+/// Both answers the old way, checking every symbol against every number.
+fn scan(schematic: &Schematic) -> (Value, Value) {
+    let symbols: Vec<_> = schematic
+        .symbols()
+        .map(|(pos, c)| ((pos.x, pos.y), c))
+        .collect();
+
+    let parts = schematic
+        .numbers
+        .iter()
+        .filter(|n| symbols.iter().any(|(s, _)| adjacent(*s, n)))
+        .map(|n| n.value)
+        .sum();
+
+    let gears = symbols
+        .iter()
+        .filter(|(_, c)| *c == '*')
+        .map(|(s, _)| {
+            schematic
+                .numbers
+                .iter()
+                .filter(|n| adjacent(*s, n))
+                .collect::<Vec<_>>()
+        })
+        .filter(|nums| nums.len() == 2)
+        .map(|pair| pair[0].value * pair[1].value)
+        .sum();
+
+    (parts, gears)
+}
+
+/// Both answers using the number index.
+fn indexed(schematic: &Schematic) -> (Value, Value) {
+    let parts = schematic
+        .numbers
+        .iter()
+        .zip(schematic.part_flags())
+        .filter(|(_, part)| *part)
+        .map(|(n, _)| n.value)
+        .sum();
+
+    let gears = schematic
+        .symbols()
+        .filter(|&(_, c)| c == '*')
+        .map(|(s, _)| schematic.numbers_around(s).collect::<Vec<_>>())
+        .filter(|nums| nums.len() == 2)
+        .map(|pair| schematic.numbers[pair[0]].value * schematic.numbers[pair[1]].value)
+        .sum();
+
+    (parts, gears)
+}
+
+/// How many numbers touch each of `symbols`, checking every number for each.
+fn scan_around(schematic: &Schematic, symbols: &[Pos]) -> usize {
+    symbols
+        .iter()
+        .map(|s| {
+            schematic
+                .numbers
+                .iter()
+                .filter(|n| adjacent((s.x, s.y), n))
+                .count()
+        })
+        .sum()
+}
+
+/// How many numbers touch each of `symbols`, using the number index.
+fn indexed_around(schematic: &Schematic, symbols: &[Pos]) -> usize {
+    symbols
+        .iter()
+        .map(|&s| schematic.numbers_around(s).count())
+        .sum()
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, f64) {
+    let start = Instant::now();
+    let result = f();
+    (
+        result,
+        Instant::now().duration_since(start).as_secs_f64() * 1000.0,
+    )
+}
+
+/// Compare both approaches on small schematics, then on a `size` square one.
+///
+/// The full scan is far too slow to run at large sizes, so there it only looks
+/// up the numbers around a sample of the symbols, with the index timed on the
+/// same sample alongside. Build with `--release` for sensible numbers.
+pub fn run(size: usize) {
+    for small in [100, 200, 400] {
+        let schematic = parse_input(&generate(small, small as u64)).unwrap();
+        let (expected, scan_ms) = time(|| scan(&schematic));
+        let (found, index_ms) = time(|| indexed(&schematic));
+        assert_eq!(expected, found, "the index disagrees with the scan");
+
+        println!(
+            "{0}x{0}: scan {1:.1}ms, index {2:.1}ms ({3:.0}x faster)",
+            small,
+            scan_ms,
+            index_ms,
+            scan_ms / index_ms
+        );
+    }
+
+    let (text, gen_ms) = time(|| generate(size, 2023));
+    let (schematic, parse_ms) = time(|| parse_input(&text).unwrap());
+    drop(text);
+    let ((parts, gears), index_ms) = time(|| indexed(&schematic));
+
+    println!(
+        "{0}x{0}: generated in {1:.0}ms, parsed in {2:.0}ms, both answers with the index {3:.0}ms \
+         (part numbers {4}, gear ratios {5})",
+        size, gen_ms, parse_ms, index_ms, parts, gears
+    );
+
+    let symbols: Vec<Pos> = schematic.symbols().map(|(pos, _)| pos).collect();
+    let step = (symbols.len() / SAMPLE).max(1);
+    let sample: Vec<Pos> = symbols.iter().copied().step_by(step).take(SAMPLE).collect();
+    let (expected, scan_ms) = time(|| scan_around(&schematic, &sample));
+    let (found, index_ms) = time(|| indexed_around(&schematic, &sample));
+    assert_eq!(expected, found, "the index disagrees with the scan");
+
+    println!(
+        "{0}x{0}: numbers around {1} of {2} symbols: scan {3:.1}ms, index {4:.1}ms ({5:.0}x faster)",
+        size,
+        sample.len(),
+        symbols.len(),
+        scan_ms,
+        index_ms,
+        scan_ms / index_ms
+    );
+}
+
+#[test]
+fn index_matches_scan() {
+    for seed in 0..5 {
+        let schematic = parse_input(&generate(60, seed)).unwrap();
+        assert_eq!(scan(&schematic), indexed(&schematic));
+
+        let symbols: Vec<Pos> = schematic.symbols().map(|(pos, _)| pos).collect();
+        assert_eq!(
+            scan_around(&schematic, &symbols),
+            indexed_around(&schematic, &symbols)
+        );
+    }
+
+    let schematic = parse_input(crate::INPUT).unwrap();
+    assert_eq!(scan(&schematic), indexed(&schematic));
+}
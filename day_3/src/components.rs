@@ -0,0 +1,266 @@
+//! Grouping numbers and symbols into clusters that touch, diagonals included.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    gears::{self, GearRule},
+    grid::Pos,
+    Schematic, Value,
+};
+
+/// Disjoint sets over `0..n`, joined by size with path halving.
+struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (big, small) = if self.size[a] >= self.size[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub count: usize,
+    /// Stops at `Value::MAX` rather than overflowing.
+    pub sum: Value,
+    pub min: Value,
+    pub max: Value,
+}
+
+impl Stats {
+    pub fn mean(&self) -> f64 {
+        self.sum as f64 / self.count as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    /// Ids of its numbers, in reading order.
+    pub numbers: Vec<usize>,
+    /// Its symbols, in reading order.
+    pub symbols: Vec<Pos>,
+    /// Top left corner of its bounding box.
+    pub min: Pos,
+    /// Bottom right corner of its bounding box, inclusive.
+    pub max: Pos,
+    /// Stats over its numbers' values, or `None` if it has no numbers.
+    pub stats: Option<Stats>,
+}
+
+/// Every cluster of non-empty cells in `schematic`, in reading order of their first cell.
+pub fn components(schematic: &Schematic) -> Vec<Component> {
+    let grid = &schematic.grid;
+    let filled = |pos: Pos| grid[pos] != '.';
+    let index = |pos: Pos| pos.y * grid.width() + pos.x;
+
+    let mut sets = UnionFind::new(grid.width() * grid.height());
+    for pos in grid.positions().filter(|&pos| filled(pos)) {
+        // Each pair only needs joining once, so just look right and below
+        for (dx, dy) in [(1, 0), (-1, 1), (0, 1), (1, 1)] {
+            if let Some(next) = grid.offset(pos, dx, dy).filter(|&next| filled(next)) {
+                sets.union(index(pos), index(next));
+            }
+        }
+    }
+
+    let mut components: BTreeMap<usize, Component> = BTreeMap::new();
+    let empty = |pos: Pos| Component {
+        numbers: Vec::new(),
+        symbols: Vec::new(),
+        min: pos,
+        max: pos,
+        stats: None,
+    };
+
+    for (pos, _) in schematic.symbols() {
+        let root = sets.find(index(pos));
+        let c = components.entry(root).or_insert_with(|| empty(pos));
+        c.symbols.push(pos);
+        stretch(c, pos);
+    }
+    for (id, num) in schematic.numbers.iter().enumerate() {
+        let pos = Pos::new(num.x, num.y);
+        let root = sets.find(index(pos));
+        let c = components.entry(root).or_insert_with(|| empty(pos));
+        c.numbers.push(id);
+        stretch(c, pos);
+        stretch(c, Pos::new(num.x + num.len - 1, num.y));
+
+        let stats = c.stats.get_or_insert(Stats {
+            count: 0,
+            sum: 0,
+            min: num.value,
+            max: num.value,
+        });
+        stats.count += 1;
+        stats.sum = stats.sum.saturating_add(num.value);
+        stats.min = stats.min.min(num.value);
+        stats.max = stats.max.max(num.value);
+    }
+
+    let mut components: Vec<Component> = components.into_values().collect();
+    components.sort_by_key(|c| {
+        let numbers = c.numbers.iter().map(|&id| {
+            let num = &schematic.numbers[id];
+            (num.y, num.x)
+        });
+        c.symbols
+            .iter()
+            .map(|pos| (pos.y, pos.x))
+            .chain(numbers)
+            .min()
+    });
+    components
+}
+
+fn stretch(c: &mut Component, pos: Pos) {
+    c.min = Pos::new(c.min.x.min(pos.x), c.min.y.min(pos.y));
+    c.max = Pos::new(c.max.x.max(pos.x), c.max.y.max(pos.y));
+}
+
+/// Each number that belongs to more than one gear under `rule`, with those gears.
+pub fn shared_numbers(schematic: &Schematic, rule: &GearRule) -> BTreeMap<usize, Vec<Pos>> {
+    let mut gears_of: BTreeMap<usize, Vec<Pos>> = BTreeMap::new();
+    for gear in gears::find_gears(schematic, rule) {
+        for id in gear.numbers {
+            gears_of.entry(id).or_default().push(gear.pos);
+        }
+    }
+    gears_of.retain(|_, gears| gears.len() > 1);
+    gears_of
+}
+
+/// `components [--top <n>] [gear rule flags]`, listing the biggest clusters and any shared numbers.
+pub fn run(schematic: &Schematic, args: &[String]) {
+    let (top, rest) = match args {
+        [flag, n, rest @ ..] if flag == "--top" => match n.parse::<usize>() {
+            Ok(n) => (n, rest),
+            Err(_) => {
+                eprintln!("Expected a count after --top, got {:?}", n);
+                return;
+            }
+        },
+        _ => (10, args),
+    };
+    let rule = match GearRule::from_args(rest) {
+        Ok(rule) => rule,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    let mut components = components(schematic);
+    let mut sizes: BTreeMap<usize, usize> = BTreeMap::new();
+    for c in &components {
+        *sizes.entry(c.numbers.len() + c.symbols.len()).or_default() += 1;
+    }
+
+    println!("{} components", components.len());
+    for (size, count) in sizes {
+        println!("  {} with {} members", count, size);
+    }
+
+    components.sort_by_key(|c| std::cmp::Reverse(c.numbers.len() + c.symbols.len()));
+    println!();
+    println!("Largest {}:", top.min(components.len()));
+    for c in components.iter().take(top) {
+        let symbols: String = c.symbols.iter().map(|&pos| schematic.grid[pos]).collect();
+        print!(
+            "  {}..{}: {} numbers, symbols {:?}",
+            c.min,
+            c.max,
+            c.numbers.len(),
+            symbols
+        );
+        match c.stats {
+            Some(s) => println!(
+                ", sum {}, min {}, max {}, mean {:.1}",
+                s.sum,
+                s.min,
+                s.max,
+                s.mean()
+            ),
+            None => println!(),
+        }
+    }
+
+    let shared = shared_numbers(schematic, &rule);
+    println!();
+    println!("{} numbers in more than one gear", shared.len());
+    for (id, gears) in shared {
+        let num = &schematic.numbers[id];
+        let gears: Vec<String> = gears.iter().map(Pos::to_string).collect();
+        println!(
+            "  {} at {}: gears {}",
+            num.value,
+            Pos::new(num.x, num.y),
+            gears.join(", ")
+        );
+    }
+}
+
+#[test]
+fn clusters() {
+    let schematic = crate::parse_input(crate::INPUT_SAMPLE).unwrap();
+    let all = components(&schematic);
+    let values = |c: &Component| -> Vec<Value> {
+        c.numbers
+            .iter()
+            .map(|&id| schematic.numbers[id].value)
+            .collect()
+    };
+
+    assert_eq!(all.len(), 8);
+    assert_eq!(values(&all[0]), vec![467, 35]);
+    assert_eq!(all[0].symbols, vec![Pos::new(3, 1)]);
+    assert_eq!((all[0].min, all[0].max), (Pos::new(0, 0), Pos::new(3, 2)));
+    assert_eq!(values(&all[1]), vec![114]);
+    assert!(all[1].symbols.is_empty());
+
+    assert_eq!(values(&all[6]), vec![755, 598]);
+    assert_eq!(
+        all[6].stats,
+        Some(Stats {
+            count: 2,
+            sum: 1353,
+            min: 598,
+            max: 755
+        })
+    );
+    assert!(shared_numbers(&schematic, &GearRule::default()).is_empty());
+
+    let shared = crate::parse_input("1.3\n*2*\n").unwrap();
+    assert_eq!(components(&shared).len(), 1);
+    assert_eq!(
+        shared_numbers(&shared, &GearRule::default()),
+        BTreeMap::from([(2, vec![Pos::new(0, 1), Pos::new(2, 1)])])
+    );
+}
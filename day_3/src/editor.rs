@@ -0,0 +1,302 @@
+//! Editing a schematic a cell at a time, keeping both answers up to date.
+
+use std::ops::RangeInclusive;
+
+use crate::{
+    gears::{self, GearRule},
+    grid::Pos,
+    is_symbol, tokenise, Num, Schematic, SchematicError, Value,
+};
+
+pub struct Editor {
+    schematic: Schematic,
+    rule: GearRule,
+    part_sum: Value,
+    gear_sum: Value,
+}
+
+impl Editor {
+    pub fn new(schematic: Schematic) -> Result<Editor, SchematicError> {
+        let rule = GearRule::default();
+        let part_sum = crate::part_numbers(&schematic)?;
+        let gear_sum = crate::gear_ratios(&schematic)?;
+
+        Ok(Editor {
+            schematic,
+            rule,
+            part_sum,
+            gear_sum,
+        })
+    }
+
+    pub fn part_sum(&self) -> Value {
+        self.part_sum
+    }
+
+    pub fn gear_sum(&self) -> Value {
+        self.gear_sum
+    }
+
+    /// Put `c` at `(x, y)`, then update both sums.
+    ///
+    /// Only row `y` is tokenised again, and only the numbers and symbols in a
+    /// small box around the numbers that changed are looked at: their share of
+    /// the sums is taken away before the edit and added back after it. When the
+    /// edges wrap, the whole schematic is looked at instead.
+    ///
+    /// An edit that would make a number or either sum too big is undone.
+    pub fn set_cell(&mut self, x: usize, y: usize, c: char) -> Result<(), String> {
+        let pos = Pos::new(x, y);
+        let old = match self.schematic.grid.get(pos) {
+            Some(&old) => old,
+            None => return Err(format!("{} is outside the schematic", pos)),
+        };
+        if old == c {
+            return Ok(());
+        }
+
+        let mut row = self.schematic.grid.row(y).to_vec();
+        row[x] = c;
+        let tokens = tokenise(y, &row).map_err(|e| e.to_string())?;
+
+        // Numbers that can have changed are the ones touching x, before or after
+        let near = |n: &&Num| n.x <= x + 1 && n.x + n.len >= x;
+        let (mut lo, mut hi) = (x, x);
+        for n in self.row_numbers(y).iter().chain(&tokens).filter(near) {
+            lo = lo.min(n.x);
+            hi = hi.max(n.x + n.len - 1);
+        }
+        let radius = self.schematic.adjacency.radius;
+        let columns = lo.saturating_sub(radius)..=hi.saturating_add(radius);
+
+        // Both sums fit before the edit, so every share of them does too
+        let (parts, gears) = self.share(y, &columns).unwrap();
+        self.part_sum -= parts;
+        self.gear_sum -= gears;
+
+        self.schematic.grid[pos] = c;
+        let old_tokens = self.replace_numbers(y, tokens);
+
+        let sums = self.share(y, &columns).and_then(|(new_parts, new_gears)| {
+            Some((
+                self.part_sum.checked_add(new_parts)?,
+                self.gear_sum.checked_add(new_gears)?,
+            ))
+        });
+        match sums {
+            Some((part_sum, gear_sum)) => {
+                self.part_sum = part_sum;
+                self.gear_sum = gear_sum;
+                Ok(())
+            }
+            None => {
+                self.schematic.grid[pos] = old;
+                self.replace_numbers(y, old_tokens);
+                self.part_sum += parts;
+                self.gear_sum += gears;
+                Err(format!(
+                    "{} at {} would make a sum too big for {} bits",
+                    c,
+                    pos,
+                    Value::BITS
+                ))
+            }
+        }
+    }
+
+    /// Swap row `y`'s numbers for `tokens`, giving back the old ones.
+    fn replace_numbers(&mut self, y: usize, tokens: Vec<Num>) -> Vec<Num> {
+        let s = &mut self.schematic;
+        let (start, end) = (s.row_starts[y], s.row_starts[y + 1]);
+        let delta = tokens.len() as isize - (end - start) as isize;
+        let old = s.numbers.splice(start..end, tokens).collect();
+        for row_start in &mut s.row_starts[y + 1..] {
+            *row_start = row_start.wrapping_add_signed(delta);
+        }
+        old
+    }
+
+    fn row_numbers(&self, y: usize) -> &[Num] {
+        let s = &self.schematic;
+        &s.numbers[s.row_starts[y]..s.row_starts[y + 1]]
+    }
+
+    fn rows_around(&self, y: usize) -> std::ops::Range<usize> {
+        let radius = self.schematic.adjacency.radius;
+        y.saturating_sub(radius)
+            ..(y.saturating_add(radius).saturating_add(1)).min(self.schematic.grid.height())
+    }
+
+    /// How much the part numbers and gears within a row of `y` and touching
+    /// `columns` add to each sum, or `None` if that's too big.
+    fn share(&self, y: usize, columns: &RangeInclusive<usize>) -> Option<(Value, Value)> {
+        // Across a wrapped edge an edit can reach anywhere, so take the lot
+        if self.schematic.adjacency.wrap {
+            let parts = crate::part_numbers(&self.schematic).ok()?;
+            return Some((parts, crate::gear_ratios(&self.schematic).ok()?));
+        }
+
+        let parts = self
+            .rows_around(y)
+            .flat_map(|y| self.row_numbers(y))
+            .filter(|n| n.x <= *columns.end() && n.x + n.len > *columns.start())
+            .filter(|n| self.is_part(n))
+            .try_fold(0 as Value, |sum, n| sum.checked_add(n.value))?;
+
+        let width = self.schematic.grid.width();
+        let gears = self
+            .rows_around(y)
+            .flat_map(|y| {
+                let row = self.schematic.grid.row(y);
+                (*columns.start()..=*columns.end().min(&(width - 1)))
+                    .filter(move |&x| is_symbol(row[x]))
+                    .map(move |x| (Pos::new(x, y), row[x]))
+            })
+            .filter_map(|(pos, c)| gears::gear_at(&self.schematic, &self.rule, pos, c))
+            .try_fold(0 as Value, |sum, gear| sum.checked_add(gear.value?))?;
+
+        Some((parts, gears))
+    }
+
+    /// Whether `n` touches a symbol, for schematics whose edges don't wrap.
+    fn is_part(&self, n: &Num) -> bool {
+        let grid = &self.schematic.grid;
+        let adjacency = self.schematic.adjacency;
+
+        adjacency
+            .rows(n.y, grid.height())
+            .into_iter()
+            .any(|(y, dy)| {
+                let reach = adjacency.reach(dy).unwrap_or(0);
+                let columns = n.x.saturating_sub(reach)
+                    ..(n.x + n.len).saturating_add(reach).min(grid.width());
+                grid.row(y)[columns].iter().any(|&c| is_symbol(c))
+            })
+    }
+}
+
+/// `edit <x,y,c>...`, applying each edit in turn and printing the sums after it.
+pub fn run(schematic: crate::Schematic, args: &[String]) {
+    let mut editor = match Editor::new(schematic) {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    println!(
+        "Start: part numbers {}, gear ratios {}",
+        editor.part_sum(),
+        editor.gear_sum()
+    );
+
+    for arg in args {
+        let mut fields = arg.splitn(3, ',');
+        let edit = (|| {
+            let x = fields.next()?.parse().ok()?;
+            let y = fields.next()?.parse().ok()?;
+            let mut c = fields.next()?.chars();
+            Some((x, y, c.next().filter(|_| c.next().is_none())?))
+        })();
+
+        let Some((x, y, c)) = edit else {
+            eprintln!("Expected an edit like 3,1,#, got {:?}", arg);
+            return;
+        };
+        if let Err(e) = editor.set_cell(x, y, c) {
+            eprintln!("{}", e);
+            return;
+        }
+        println!(
+            "{} {:?}: part numbers {}, gear ratios {}",
+            Pos::new(x, y),
+            c,
+            editor.part_sum(),
+            editor.gear_sum()
+        );
+    }
+}
+
+#[test]
+fn edits_match_reparsing() {
+    let mut editor = Editor::new(crate::parse_input(crate::INPUT_SAMPLE).unwrap()).unwrap();
+    let check = |editor: &Editor| {
+        let text: String = editor
+            .schematic
+            .grid
+            .rows()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect();
+        let fresh = crate::parse_input(&text)
+            .unwrap()
+            .with_adjacency(editor.schematic.adjacency);
+        assert_eq!(Ok(editor.part_sum()), crate::part_numbers(&fresh));
+        assert_eq!(Ok(editor.gear_sum()), crate::gear_ratios(&fresh));
+    };
+
+    // Break the gear at (3, 1), then join 467 and 114 into one number
+    editor.set_cell(3, 1, '.').unwrap();
+    assert_eq!(editor.gear_sum(), 467835 - 16345);
+    for x in 3..5 {
+        editor.set_cell(x, 0, '1').unwrap();
+    }
+    check(&editor);
+    assert!(editor.set_cell(10, 0, '1').is_err());
+
+    // Keep column 4 (and 9, already empty) clear of digits so numbers stay short
+    for y in 0..10 {
+        editor.set_cell(4, y, '.').unwrap();
+        check(&editor);
+    }
+    let scramble = |editor: &mut Editor, mut seed: u64, edits: usize| {
+        for _ in 0..edits {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let x = [0, 1, 2, 3, 5, 6, 7, 8][(seed >> 33) as usize % 8];
+            let y = (seed >> 45) as usize % 10;
+            let c = ['.', '.', '*', '#', '1', '5', '9'][(seed >> 57) as usize % 7];
+            editor.set_cell(x, y, c).unwrap();
+            check(editor);
+        }
+    };
+    scramble(&mut editor, 7, 500);
+
+    for (seed, adjacency) in ["orthogonal:2", "manhattan:3", "chebyshev:2,wrap"]
+        .into_iter()
+        .enumerate()
+    {
+        let schematic = crate::parse_input(crate::INPUT_SAMPLE)
+            .unwrap()
+            .with_adjacency(adjacency.parse().unwrap());
+        let mut editor = Editor::new(schematic).unwrap();
+        for y in 0..10 {
+            editor.set_cell(4, y, '.').unwrap();
+        }
+        check(&editor);
+        scramble(&mut editor, seed as u64, 200);
+    }
+
+    // A radius far past the edges just reaches everything
+    let schematic = crate::parse_input(crate::INPUT_SAMPLE)
+        .unwrap()
+        .with_adjacency(format!("manhattan:{}", usize::MAX).parse().unwrap());
+    let mut editor = Editor::new(schematic).unwrap();
+    editor.set_cell(9, 9, '#').unwrap();
+    check(&editor);
+
+    // Edits making a number or a sum too big are refused, leaving the schematic as it was
+    let digits = Value::MAX.to_string();
+    let len = digits.len();
+    let text = format!("{}..\n{}\n", digits, ".".repeat(len + 2));
+    let mut editor = Editor::new(crate::parse_input(&text).unwrap()).unwrap();
+
+    assert!(editor.set_cell(len, 0, '0').is_err());
+    assert_eq!(editor.schematic.grid[Pos::new(len, 0)], '.');
+    editor.set_cell(len, 0, '*').unwrap();
+    assert_eq!(editor.part_sum(), Value::MAX);
+    assert!(editor.set_cell(len + 1, 1, '1').is_err());
+    assert_eq!(editor.schematic.grid[Pos::new(len + 1, 1)], '.');
+    assert_eq!((editor.part_sum(), editor.gear_sum()), (Value::MAX, 0));
+    check(&editor);
+}
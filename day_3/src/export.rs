@@ -0,0 +1,291 @@
+//! Drawing the schematic to SVG, or to PNG with a small rasteriser of our own.
+
+use crate::{
+    gears::{self, Gear, GearRule},
+    grid::Pos,
+    render::{self, Class},
+    Schematic,
+};
+
+const BACKGROUND: [u8; 3] = [0x1e, 0x1e, 0x24];
+const LINK: [u8; 3] = [0xf0, 0xf0, 0xf0];
+
+fn colour(class: Class) -> [u8; 3] {
+    match class {
+        Class::Empty => BACKGROUND,
+        Class::Part => [0x3c, 0xb3, 0x4a],
+        Class::Loose => [0xd6, 0x45, 0x45],
+        Class::Symbol => [0xe0, 0xb8, 0x30],
+        Class::Gear => [0xc0, 0x50, 0xe0],
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// The middle of the cell at `pos`, in cells.
+fn centre(pos: Pos) -> (f64, f64) {
+    (pos.x as f64 + 0.5, pos.y as f64 + 0.5)
+}
+
+/// Each line from a gear to one of its numbers, as `(from, to)` in cells.
+fn links<'a>(
+    schematic: &'a Schematic,
+    gears: &'a [Gear],
+) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + 'a {
+    gears.iter().flat_map(move |gear| {
+        gear.numbers.iter().map(move |&id| {
+            let num = &schematic.numbers[id];
+            let to = (num.x as f64 + num.len as f64 / 2.0, num.y as f64 + 0.5);
+            (centre(gear.pos), to)
+        })
+    })
+}
+
+/// An SVG of `schematic`, `cell` pixels to a character.
+pub fn svg(schematic: &Schematic, cell: usize) -> String {
+    let classes = render::classify(schematic);
+    let gears = gears::find_gears(schematic, &GearRule::default());
+    let (width, height) = (
+        schematic.grid.width() * cell,
+        schematic.grid.height() * cell,
+    );
+    let px = |v: f64| v * cell as f64;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    out += &format!(
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        hex(BACKGROUND)
+    );
+
+    out += &format!(
+        "<g font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+        cell
+    );
+    for (pos, &class) in classes.iter().filter(|(_, &c)| c != Class::Empty) {
+        let (x, y) = centre(pos);
+        let text = match schematic.grid[pos] {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            c => c.to_string(),
+        };
+        out += &format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>\n",
+            px(x),
+            px(y),
+            hex(colour(class)),
+            text
+        );
+    }
+    out += "</g>\n";
+
+    out += &format!(
+        "<g stroke=\"{}\" stroke-width=\"{}\" stroke-opacity=\"0.6\">\n",
+        hex(LINK),
+        cell as f64 / 8.0
+    );
+    for ((x1, y1), (x2, y2)) in links(schematic, &gears) {
+        out += &format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+            px(x1),
+            px(y1),
+            px(x2),
+            px(y2)
+        );
+    }
+    out += "</g>\n</svg>\n";
+
+    out
+}
+
+/// RGB pixels, row by row.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, rgb: [u8; 3]) {
+        for row in y..(y + h).min(self.height) {
+            let start = row * self.width;
+            self.pixels[start + x.min(self.width)..start + (x + w).min(self.width)].fill(rgb);
+        }
+    }
+
+    /// A one pixel wide line, by Bresenham.
+    fn line(&mut self, from: (i64, i64), to: (i64, i64), rgb: [u8; 3]) {
+        let (mut x, mut y) = from;
+        let (dx, dy) = ((to.0 - x).abs(), -(to.1 - y).abs());
+        let (sx, sy) = ((to.0 - x).signum(), (to.1 - y).signum());
+        let mut err = dx + dy;
+
+        loop {
+            if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+                self.pixels[y as usize * self.width + x as usize] = rgb;
+            }
+            if (x, y) == to {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+}
+
+/// A PNG of `schematic`, with every character a `cell` pixel square.
+///
+/// There's no font, so cells are just blocks of colour.
+pub fn png(schematic: &Schematic, cell: usize) -> Vec<u8> {
+    let classes = render::classify(schematic);
+    let gears = gears::find_gears(schematic, &GearRule::default());
+    let (width, height) = (
+        schematic.grid.width() * cell,
+        schematic.grid.height() * cell,
+    );
+
+    let mut canvas = Canvas {
+        width,
+        height,
+        pixels: vec![BACKGROUND; width * height],
+    };
+    // Leave a pixel of gap between cells once they're big enough to see it
+    let block = if cell > 2 { cell - 1 } else { cell };
+    for (pos, &class) in classes.iter().filter(|(_, &c)| c != Class::Empty) {
+        canvas.fill(pos.x * cell, pos.y * cell, block, block, colour(class));
+    }
+    let px = |(x, y): (f64, f64)| ((x * cell as f64) as i64, (y * cell as f64) as i64);
+    for (from, to) in links(schematic, &gears) {
+        canvas.line(px(from), px(to), LINK);
+    }
+
+    encode_png(&canvas)
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// An 8-bit RGB PNG, with the image data in uncompressed deflate blocks.
+fn encode_png(canvas: &Canvas) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(canvas.height * (canvas.width * 3 + 1));
+    for row in canvas.pixels.chunks(canvas.width.max(1)) {
+        // Filter type 0: the row as it is
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((canvas.width as u32).to_be_bytes());
+    header.extend((canvas.height as u32).to_be_bytes());
+    // 8 bits per channel, RGB, deflate, no filter method, no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib);
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// `export <file.svg|file.png> [--cell <pixels>]`
+pub fn run(schematic: &Schematic, args: &[String]) {
+    let (path, cell) = match args {
+        [path] => (path, None),
+        [path, flag, cell] if flag == "--cell" => (path, cell.parse::<usize>().ok()),
+        _ => {
+            eprintln!("Usage: export <file.svg|file.png> [--cell <pixels>]");
+            return;
+        }
+    };
+
+    let bytes = if path.ends_with(".svg") {
+        svg(schematic, cell.unwrap_or(12)).into_bytes()
+    } else if path.ends_with(".png") {
+        png(schematic, cell.unwrap_or(4))
+    } else {
+        eprintln!("Can't tell what to write from {:?}, use .svg or .png", path);
+        return;
+    };
+
+    match std::fs::write(path, bytes) {
+        Ok(()) => println!("Wrote {}", path),
+        Err(e) => eprintln!("Couldn't write {}: {}", path, e),
+    }
+}
+
+#[test]
+fn exports() {
+    let schematic = crate::parse_input(crate::INPUT_SAMPLE).unwrap();
+
+    let svg = svg(&schematic, 10);
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"100\"")
+    );
+    assert_eq!(svg.matches("<line ").count(), 4);
+    assert!(svg.contains("<line x1=\"35\" y1=\"15\" x2=\"15\" y2=\"5\"/>"));
+    assert_eq!(svg.matches("<text ").count(), 34);
+
+    assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+    let png = png(&schematic, 3);
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[16..24], &[0, 0, 0, 30, 0, 0, 0, 30]);
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+}
@@ -0,0 +1,208 @@
+use std::str::FromStr;
+
+use crate::{grid::Pos, Schematic, Value};
+
+/// How many numbers a symbol has to touch to count as a gear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn allows(&self, n: usize) -> bool {
+        match *self {
+            Arity::Exactly(arity) => n == arity,
+            Arity::AtLeast(arity) => n >= arity,
+        }
+    }
+}
+
+/// How a gear's numbers make its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    Product,
+    Sum,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    pub arity: Arity,
+    pub combine: Combine,
+}
+
+/// The puzzle's rule: a `*` touching exactly two numbers, worth their product.
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            arity: Arity::Exactly(2),
+            combine: Combine::Product,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gear {
+    pub pos: Pos,
+    pub symbol: char,
+    /// Ids of the numbers it touches.
+    pub numbers: Vec<usize>,
+    /// `None` if it's too big for a `Value`.
+    pub value: Option<Value>,
+}
+
+/// Every symbol in `schematic` that is a gear under `rule`, in reading order.
+pub fn find_gears(schematic: &Schematic, rule: &GearRule) -> Vec<Gear> {
+    schematic
+        .symbols()
+        .filter_map(|(pos, symbol)| gear_at(schematic, rule, pos, symbol))
+        .collect()
+}
+
+/// The gear made by `symbol` at `pos`, if it is one under `rule`.
+pub fn gear_at(schematic: &Schematic, rule: &GearRule, pos: Pos, symbol: char) -> Option<Gear> {
+    if !rule.symbols.contains(&symbol) {
+        return None;
+    }
+
+    let numbers: Vec<usize> = schematic.numbers_around(pos).collect();
+    if !rule.arity.allows(numbers.len()) {
+        return None;
+    }
+
+    let mut values = numbers.iter().map(|&id| schematic.numbers[id].value);
+    let value = match rule.combine {
+        Combine::Product => values.try_fold(1, Value::checked_mul),
+        Combine::Sum => values.try_fold(0, Value::checked_add),
+        Combine::Max => Some(values.max().unwrap_or(0)),
+    };
+
+    Some(Gear {
+        pos,
+        symbol,
+        numbers,
+        value,
+    })
+}
+
+impl FromStr for Arity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (n, at_least) = match s.strip_suffix('+') {
+            Some(n) => (n, true),
+            None => (s, false),
+        };
+        let n = n
+            .parse()
+            .map_err(|_| format!("Expected an arity like 2 or 2+, got {:?}", s))?;
+
+        Ok(if at_least {
+            Arity::AtLeast(n)
+        } else {
+            Arity::Exactly(n)
+        })
+    }
+}
+
+impl FromStr for Combine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Combine::Product),
+            "sum" => Ok(Combine::Sum),
+            "max" => Ok(Combine::Max),
+            _ => Err(format!("Unknown way to combine numbers {:?}", s)),
+        }
+    }
+}
+
+impl GearRule {
+    /// Read `--symbols <chars>`, `--arity <n|n+>` and `--combine <product|sum|max>`.
+    pub fn from_args(args: &[String]) -> Result<GearRule, String> {
+        let mut rule = GearRule::default();
+
+        for pair in args.chunks(2) {
+            match pair {
+                [flag, symbols] if flag == "--symbols" => rule.symbols = symbols.chars().collect(),
+                [flag, arity] if flag == "--arity" => rule.arity = arity.parse()?,
+                [flag, combine] if flag == "--combine" => rule.combine = combine.parse()?,
+                _ => return Err(format!("Bad argument {:?}", pair.join(" "))),
+            }
+        }
+
+        Ok(rule)
+    }
+}
+
+/// List every gear under the rule given in `args`.
+pub fn run(schematic: &Schematic, args: &[String]) {
+    let rule = match GearRule::from_args(args) {
+        Ok(rule) => rule,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    let gears = find_gears(schematic, &rule);
+    for gear in &gears {
+        let numbers: Vec<String> = gear
+            .numbers
+            .iter()
+            .map(|&id| schematic.numbers[id].value.to_string())
+            .collect();
+        let value = match gear.value {
+            Some(value) => value.to_string(),
+            None => "too big".to_string(),
+        };
+        println!(
+            "{} {}: [{}] => {}",
+            gear.pos,
+            gear.symbol,
+            numbers.join(", "),
+            value
+        );
+    }
+
+    let total = gears
+        .iter()
+        .try_fold(0 as Value, |sum, g| sum.checked_add(g.value?));
+    match total {
+        Some(total) => println!("{} gears, total {}", gears.len(), total),
+        None => println!("{} gears, total too big", gears.len()),
+    }
+}
+
+#[test]
+fn rules() {
+    let schematic = crate::parse_input(crate::INPUT_SAMPLE).unwrap();
+    let total = |rule: &GearRule| {
+        find_gears(&schematic, rule)
+            .iter()
+            .map(|g| g.value.unwrap())
+            .sum::<Value>()
+    };
+
+    assert_eq!(total(&GearRule::default()), 467835);
+
+    let any_symbol = GearRule {
+        symbols: "*#+$".chars().collect(),
+        arity: Arity::AtLeast(1),
+        combine: Combine::Sum,
+    };
+    assert_eq!(total(&any_symbol), 4361);
+
+    let lonely = GearRule {
+        arity: Arity::Exactly(1),
+        combine: Combine::Max,
+        ..Default::default()
+    };
+    let gears = find_gears(&schematic, &lonely);
+    assert_eq!(gears.len(), 1);
+    assert_eq!((gears[0].pos, gears[0].value), (Pos::new(3, 4), Some(617)));
+}
@@ -0,0 +1,337 @@
+//! Building schematics whose answers are known in advance.
+
+use crate::{
+    gear_ratios,
+    grid::{Grid, Pos},
+    parse_input, part_numbers, Value,
+};
+
+/// Splitmix64, used by both the generator and the benchmarks so their
+/// schematics can be rebuilt from a seed.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number with exactly `digits` digits.
+    fn number(&mut self, digits: u32) -> Value {
+        let low = Value::pow(10, digits - 1);
+        low + (self.next_u64() as Value) % (low * 9)
+    }
+
+    fn symbol(&mut self) -> char {
+        // No '*', so these can never turn into gears by accident
+        const SYMBOLS: &[u8] = b"#+$/@%=&-";
+        SYMBOLS[self.below(SYMBOLS.len())] as char
+    }
+}
+
+/// A few rows of cells to put down together, `.` for empty.
+type Motif = Vec<Vec<char>>;
+
+fn motif(rows: &[String]) -> Motif {
+    rows.iter().map(|row| row.chars().collect()).collect()
+}
+
+/// A schematic being filled in, keeping every motif at least one empty cell
+/// away from the others so they can't touch.
+struct Canvas {
+    grid: Grid<char>,
+    /// Where to start looking for room, since everything before it is full.
+    cursor: Pos,
+}
+
+impl Canvas {
+    fn fits(&self, motif: &Motif, at: Pos) -> bool {
+        let width = motif.iter().map(Vec::len).max().unwrap_or(0);
+        if at.x + width > self.grid.width() || at.y + motif.len() > self.grid.height() {
+            return false;
+        }
+
+        let rows = at.y.saturating_sub(1)..(at.y + motif.len() + 1).min(self.grid.height());
+        let columns = at.x.saturating_sub(1)..(at.x + width + 1).min(self.grid.width());
+        rows.into_iter()
+            .all(|y| self.grid.row(y)[columns.clone()].iter().all(|&c| c == '.'))
+    }
+
+    fn put(&mut self, motif: &Motif, at: Pos) {
+        for (dy, row) in motif.iter().enumerate() {
+            for (dx, &c) in row.iter().enumerate().filter(|(_, &c)| c != '.') {
+                self.grid[Pos::new(at.x + dx, at.y + dy)] = c;
+            }
+        }
+    }
+
+    /// Put `motif` at the first of `spots` it fits in.
+    fn place_at(&mut self, motif: &Motif, mut spots: impl Iterator<Item = Pos>) -> bool {
+        match spots.find(|&at| self.fits(motif, at)) {
+            Some(at) => {
+                self.put(motif, at);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Put `motif` in the first gap after the cursor, in reading order.
+    fn place(&mut self, motif: &Motif) -> bool {
+        let (width, height) = (self.grid.width(), self.grid.height());
+        let start = self.cursor;
+        let mut spots = (start.y..height).flat_map(move |y| {
+            let first = if y == start.y { start.x } else { 0 };
+            (first..width).map(move |x| Pos::new(x, y))
+        });
+
+        match spots.find(|&at| self.fits(motif, at)) {
+            Some(at) => {
+                self.put(motif, at);
+                self.cursor = at;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Pairs of numbers below 1000 whose products add up to exactly `target`.
+///
+/// Each pair is the biggest product that still fits in what's left, so the
+/// leftover is always smaller than the pair's first number and soon runs out.
+/// They're worked out one at a time, as a big target needs a great many.
+fn gear_pairs(mut target: Value) -> impl Iterator<Item = (Value, Value)> {
+    std::iter::from_fn(move || {
+        if target == 0 {
+            return None;
+        }
+        let pair = (1..1000)
+            .rev()
+            .map(|a: Value| (a, (target / a).min(999)))
+            .filter(|&(_, b)| b > 0)
+            .max_by_key(|&(a, b)| a * b)
+            .unwrap();
+        target -= pair.0 * pair.1;
+        Some(pair)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated {
+    pub text: String,
+    /// What the schematic's sums actually came to.
+    pub parts: Value,
+    pub gears: Value,
+}
+
+/// A `width` by `height` schematic whose part numbers and gear ratios add up
+/// to `parts` and `gears`, or as near as there's room for.
+///
+/// The gear ratios come first, and their numbers count towards the part
+/// numbers, so `parts` can't be hit if it's smaller than those. Alongside the
+/// ordinary parts and gears go some awkward cases: numbers against every
+/// edge, a number touching its symbol only diagonally, a `*` with three
+/// numbers and one with just one, and numbers and symbols touching nothing.
+pub fn generate(
+    width: usize,
+    height: usize,
+    parts: Value,
+    gears: Value,
+    rng: &mut Rng,
+) -> Generated {
+    let mut canvas = Canvas {
+        grid: Grid::new(width, height, '.'),
+        cursor: Pos::new(0, 0),
+    };
+
+    // The gears go first, and only the ones that fit count against the parts
+    let mut budget = parts;
+    for (a, b) in gear_pairs(gears) {
+        let gear = if rng.below(2) == 0 {
+            motif(&[format!("{}*{}", a, b)])
+        } else {
+            motif(&[a.to_string(), "*".to_string(), b.to_string()])
+        };
+        if !canvas.place(&gear) {
+            break;
+        }
+        budget = budget.saturating_sub(a + b);
+    }
+
+    // The awkward cases, each only if its numbers fit in what's left of the
+    // budget, and some of them only in particular spots
+    let right_edge = (0..height).map(|y| Pos::new(width.saturating_sub(3), y));
+    let bottom_edge = (0..width)
+        .rev()
+        .map(|x| Pos::new(x, height.saturating_sub(2)));
+    let (corner, right, bottom) = (rng.number(2), rng.number(2), rng.number(2));
+    let (a, b, c) = (rng.number(2), rng.number(2), rng.number(1));
+    let (diagonal, lonely) = (rng.number(2), rng.number(3));
+    let tricky: Vec<(Motif, Value, Option<Vec<Pos>>)> = vec![
+        (
+            motif(&[format!("{}{}", corner, rng.symbol())]),
+            corner,
+            Some(vec![Pos::new(0, 0)]),
+        ),
+        (
+            motif(&[format!("{}{}", rng.symbol(), right)]),
+            right,
+            Some(right_edge.collect()),
+        ),
+        (
+            motif(&[format!("{}..", rng.symbol()), format!(".{}", bottom)]),
+            bottom,
+            Some(bottom_edge.collect()),
+        ),
+        (
+            motif(&[
+                format!("{}.{}", a, b),
+                "..*..".to_string(),
+                format!("..{}..", c),
+            ]),
+            a + b + c,
+            None,
+        ),
+        (
+            motif(&[format!("{}.", diagonal), format!("..{}", rng.symbol())]),
+            diagonal,
+            None,
+        ),
+        (motif(&[format!("{}*", lonely)]), lonely, None),
+    ];
+
+    for (motif, value, spots) in tricky {
+        if value > budget {
+            continue;
+        }
+        let placed = match spots {
+            Some(spots) => canvas.place_at(&motif, spots.into_iter()),
+            None => canvas.place(&motif),
+        };
+        if placed {
+            budget -= value;
+        }
+    }
+
+    while budget > 0 {
+        let value = if budget < 1000 { budget } else { rng.number(3) };
+        let symbol = rng.symbol();
+        let part = match rng.below(3) {
+            0 => motif(&[format!("{}{}", value, symbol)]),
+            1 => motif(&[format!("{}{}", symbol, value)]),
+            _ => motif(&[value.to_string(), format!(".{}", symbol)]),
+        };
+        if !canvas.place(&part) {
+            break;
+        }
+        budget -= value;
+    }
+
+    // Some things that touch nothing, at least one of each if there's still room
+    canvas.place(&motif(&[rng.number(3).to_string()]));
+    canvas.place(&motif(&[rng.symbol().to_string()]));
+    for _ in 0..(width * height / 100).max(2) {
+        let decoy = match rng.below(2) {
+            0 => motif(&[rng.number(3).to_string()]),
+            _ => motif(&[rng.symbol().to_string()]),
+        };
+        let at = Pos::new(rng.below(width.max(1)), rng.below(height.max(1)));
+        canvas.place_at(&decoy, std::iter::once(at));
+    }
+
+    let text: String = canvas
+        .grid
+        .rows()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect();
+    let schematic = parse_input(&text).unwrap();
+
+    Generated {
+        parts: part_numbers(&schematic).unwrap(),
+        gears: gear_ratios(&schematic).unwrap(),
+        text,
+    }
+}
+
+/// `generate <width> <height> <part sum> <gear sum> [seed]`
+///
+/// The schematic goes to stdout and how close it came to stderr, so it can be
+/// redirected straight into a file.
+pub fn run(args: &[String]) {
+    let parsed = (|| {
+        let size = |i: usize| args.get(i)?.parse::<usize>().ok();
+        let target = |i: usize| args.get(i)?.parse::<Value>().ok();
+        let seed = match args.get(4) {
+            Some(seed) => seed.parse().ok()?,
+            None => 2023,
+        };
+        (args.len() <= 5).then_some((size(0)?, size(1)?, target(2)?, target(3)?, seed))
+    })();
+    let Some((width, height, parts, gears, seed)) = parsed else {
+        eprintln!("Usage: generate <width> <height> <part sum> <gear sum> [seed]");
+        return;
+    };
+
+    let generated = generate(width, height, parts, gears, &mut Rng::new(seed));
+    print!("{}", generated.text);
+    eprintln!(
+        "Part numbers {} (target {}), gear ratios {} (target {})",
+        generated.parts, parts, generated.gears, gears
+    );
+}
+
+#[test]
+fn targets() {
+    for seed in 0..10u64 {
+        let (parts, gears) = (25_000 + Value::from(seed), 1_234_567 + Value::from(seed));
+        let generated = generate(40, 30, parts, gears, &mut Rng::new(seed));
+        assert_eq!((generated.parts, generated.gears), (parts, gears));
+
+        let schematic = parse_input(&generated.text).unwrap();
+        let grid = &schematic.grid;
+        assert!(schematic.numbers.iter().any(|n| n.x == 0 && n.y == 0));
+        assert!(schematic
+            .numbers
+            .iter()
+            .any(|n| n.x + n.len == grid.width()));
+        assert!(schematic.numbers.iter().any(|n| n.y == grid.height() - 1));
+        assert!(schematic
+            .symbols()
+            .any(|(pos, c)| c == '*' && schematic.numbers_around(pos).count() == 3));
+        assert!(schematic
+            .numbers
+            .iter()
+            .zip(schematic.part_flags())
+            .any(|(_, part)| !part));
+    }
+
+    // Too small to fit it all, so it gets as near as it can without going over
+    let generated = generate(8, 8, 1_000_000, 0, &mut Rng::new(1));
+    assert!(generated.parts > 0 && generated.parts < 1_000_000);
+
+    // Fewer parts than the gears need
+    let generated = generate(20, 20, 0, 500, &mut Rng::new(2));
+    assert_eq!(generated.gears, 500);
+
+    // No room for the gears, but plenty for the parts they'd have counted towards
+    let generated = generate(2, 2, 5, 5, &mut Rng::new(3));
+    assert_eq!((generated.parts, generated.gears), (5, 0));
+    let generated = generate(12, 4, 3000, 900_000, &mut Rng::new(4));
+    assert!(generated.parts <= 3000 && generated.parts > 2000);
+
+    // Far more gears than there's room for stops as soon as one doesn't fit
+    let generated = generate(10, 10, 0, 100_000_000_000_000, &mut Rng::new(5));
+    assert!(generated.gears > 0);
+}
@@ -0,0 +1,261 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Pos {
+    pub fn new(x: usize, y: usize) -> Pos {
+        Pos { x, y }
+    }
+}
+
+impl Display for Pos {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+// Only parse() reports it, and day 3 pads its rows instead
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// Row `row` has `found` cells rather than the `expected` of the first row.
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} is {} wide, but the grid is {} wide",
+                row + 1,
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Like `parse`, but rows shorter than the longest are filled out with `fill`.
+    pub fn parse_padded(input: &str, fill: T, mut f: impl FnMut(char) -> T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let rows: Vec<Vec<T>> = input
+            .lines()
+            .map(|line| line.chars().map(&mut f).collect())
+            .collect();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let height = rows.len();
+
+        let mut cells = Vec::with_capacity(width * height);
+        for mut row in rows {
+            row.resize(width, fill.clone());
+            cells.extend(row);
+        }
+
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.y * self.width + pos.x])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.y * self.width + pos.x])
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Pos::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// `pos` moved by `(dx, dy)`, if that's still inside the grid.
+    pub fn offset(&self, pos: Pos, dx: isize, dy: isize) -> Option<Pos> {
+        let x = pos.x.checked_add_signed(dx)?;
+        let y = pos.y.checked_add_signed(dy)?;
+        let pos = Pos::new(x, y);
+        self.contains(pos).then_some(pos)
+    }
+}
+
+// Kept for the grid puzzles still to come, though day 3 has no use for them
+#[allow(dead_code)]
+impl<T> Grid<T> {
+    /// Build a grid from text, one row per line, turning each character into a cell with `f`.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> T) -> Result<Grid<T>, GridError> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for (row, line) in input.lines().enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut f));
+
+            let found = cells.len() - before;
+            let expected = *width.get_or_insert(found);
+            if found != expected {
+                return Err(GridError::Ragged {
+                    row,
+                    expected,
+                    found,
+                });
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        (0..self.height).map(move |y| &self[Pos::new(x, y)])
+    }
+
+    /// The cells above, below, left and right of `pos` that are inside the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+        ORTHOGONAL
+            .iter()
+            .filter_map(move |&(dx, dy)| self.offset(pos, dx, dy))
+    }
+
+    /// The cells around `pos`, including diagonals, that are inside the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        const SURROUNDING: [(isize, isize); 8] = [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ];
+        SURROUNDING
+            .iter()
+            .filter_map(move |&(dx, dy)| self.offset(pos, dx, dy))
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: Pos) -> &Self::Output {
+        self.get(index)
+            .unwrap_or_else(|| panic!("{} is outside the grid", index))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, index: Pos) -> &mut Self::Output {
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("{} is outside the grid", index))
+    }
+}
+
+#[test]
+fn grid() {
+    let grid = Grid::parse("abc\ndef\r\n", |c| c).unwrap();
+
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Pos::new(1, 1)], 'e');
+    assert_eq!(grid.get(Pos::new(3, 0)), None);
+    assert_eq!(grid.row(1), &['d', 'e', 'f']);
+    assert_eq!(grid.column(2).collect::<String>(), "cf");
+    assert_eq!(
+        grid.neighbours4(Pos::new(0, 0)).collect::<Vec<_>>(),
+        vec![Pos::new(1, 0), Pos::new(0, 1)]
+    );
+    assert_eq!(grid.neighbours8(Pos::new(1, 0)).count(), 5);
+    assert_eq!(grid.neighbours8(Pos::new(1, 1)).count(), 5);
+
+    assert_eq!(
+        Grid::parse_padded("ab\nabc", '.', |c| c).row(0),
+        &['a', 'b', '.']
+    );
+    assert_eq!(
+        Grid::parse("ab\nabc", |c| c),
+        Err(GridError::Ragged {
+            row: 1,
+            expected: 2,
+            found: 3
+        })
+    );
+}
@@ -0,0 +1,155 @@
+//! Checking a schematic's text before it's parsed, since copy-pasted inputs pick up all sorts.
+
+use std::fmt::Display;
+
+/// Something about the text that would make it the wrong shape, or full of the wrong things.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    Tab {
+        row: usize,
+        col: usize,
+    },
+    /// A space in the middle of a row.
+    Space {
+        row: usize,
+        col: usize,
+    },
+    /// Whitespace from `col` to the end of the row.
+    TrailingWhitespace {
+        row: usize,
+        col: usize,
+    },
+    /// An empty line before the last row.
+    BlankLine {
+        row: usize,
+    },
+    /// Row `row` is `found` wide rather than the `expected` of the first row.
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::Tab { row, col } => write!(f, "Row {}, column {}: tab", row + 1, col + 1),
+            Issue::Space { row, col } => write!(f, "Row {}, column {}: space", row + 1, col + 1),
+            Issue::TrailingWhitespace { row, col } => write!(
+                f,
+                "Row {}, column {}: trailing whitespace",
+                row + 1,
+                col + 1
+            ),
+            Issue::BlankLine { row } => write!(f, "Row {}: blank line", row + 1),
+            Issue::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {}, column {}: row is {} wide, but the first row is {} wide",
+                row + 1,
+                expected.min(found) + 1,
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+/// `input` with `\n` line endings and no trailing blank lines, or every issue found in it.
+///
+/// With `pad`, rows shorter than the longest are filled out with `.` rather
+/// than being an issue.
+pub fn load(input: &str, pad: bool) -> Result<String, Vec<Issue>> {
+    let input = input.replace("\r\n", "\n").replace('\r', "\n");
+    let rows: Vec<&str> = input.trim_end_matches('\n').split('\n').collect();
+    let mut issues = Vec::new();
+
+    for (row, line) in rows.iter().enumerate() {
+        if line.is_empty() {
+            issues.push(Issue::BlankLine { row });
+            continue;
+        }
+
+        let content = line.trim_end_matches(char::is_whitespace).chars().count();
+        if content < line.chars().count() {
+            issues.push(Issue::TrailingWhitespace { row, col: content });
+        }
+        for (col, c) in line.chars().enumerate() {
+            match c {
+                '\t' => issues.push(Issue::Tab { row, col }),
+                ' ' if col < content => issues.push(Issue::Space { row, col }),
+                _ => {}
+            }
+        }
+    }
+
+    let widths = rows.iter().map(|line| line.chars().count());
+    let width = widths.clone().max().unwrap_or(0);
+    if !pad {
+        let expected = rows.first().map_or(0, |line| line.chars().count());
+        issues.extend(
+            widths
+                .enumerate()
+                .filter(|&(row, found)| found != expected && !rows[row].is_empty())
+                .map(|(row, found)| Issue::Ragged {
+                    row,
+                    expected,
+                    found,
+                }),
+        );
+    }
+
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+
+    let mut text = String::with_capacity(rows.len() * (width + 1));
+    for line in rows {
+        text += line;
+        text.extend(std::iter::repeat_n('.', width - line.chars().count()));
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+#[test]
+fn loading() {
+    assert_eq!(load("4.\r\n.*\r\n\r\n", false), Ok("4.\n.*\n".to_string()));
+    assert_eq!(load("4.\r.*", false), Ok("4.\n.*\n".to_string()));
+
+    assert_eq!(
+        load("467..\n...*\n..35.\n", false),
+        Err(vec![Issue::Ragged {
+            row: 1,
+            expected: 5,
+            found: 4
+        }])
+    );
+    assert_eq!(
+        load("467..\n...*\n..35.\n", true),
+        Ok("467..\n...*.\n..35.\n".to_string())
+    );
+
+    assert_eq!(
+        load("4\t6 7. \n\n..*..\n", true),
+        Err(vec![
+            Issue::TrailingWhitespace { row: 0, col: 6 },
+            Issue::Tab { row: 0, col: 1 },
+            Issue::Space { row: 0, col: 3 },
+            Issue::BlankLine { row: 1 },
+        ])
+    );
+    assert_eq!(
+        Issue::Ragged {
+            row: 1,
+            expected: 5,
+            found: 4
+        }
+        .to_string(),
+        "Row 2, column 5: row is 4 wide, but the first row is 5 wide"
+    );
+}
@@ -1,95 +1,362 @@
+mod adjacency;
+mod bench;
+mod components;
+mod editor;
+mod export;
+mod gears;
+mod generate;
+mod grid;
+mod load;
+mod render;
+
+use std::fmt::Display;
+
+use adjacency::Adjacency;
+use gears::GearRule;
+use grid::{Grid, Pos};
+
+/// What numbers, gear ratios and sums are read into.
+#[cfg(not(feature = "wide"))]
+type Value = u64;
+#[cfg(feature = "wide")]
+type Value = u128;
+
 const INPUT: &str = include_str!("input.txt");
+#[cfg(test)]
+const INPUT_SAMPLE: &str = include_str!("input_sample.txt");
 
 fn main() {
-    part_1();
-    part_2();
-}
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
 
-struct Symbol {
-    x: usize,
-    y: usize,
-    value: char,
+    // Numbers touch symbols in the puzzle's way unless --adjacency says otherwise
+    let adjacency = match args.iter().position(|a| a == "--adjacency") {
+        Some(i) if i + 1 < args.len() => {
+            let spec: Vec<String> = args.drain(i..i + 2).collect();
+            match spec[1].parse() {
+                Ok(adjacency) => adjacency,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            }
+        }
+        Some(_) => {
+            eprintln!("Expected an adjacency like chebyshev:1 or manhattan:2,wrap");
+            return;
+        }
+        None => Adjacency::default(),
+    };
+
+    // Ragged rows are an error unless --pad is given
+    let pad = args.iter().any(|a| a == "--pad");
+    args.retain(|a| a != "--pad");
+
+    // The schematic is the puzzle input unless --input names a file
+    let input = match args.iter().position(|a| a == "--input") {
+        Some(i) if i + 1 < args.len() => {
+            let path: Vec<String> = args.drain(i..i + 2).collect();
+            match std::fs::read_to_string(&path[1]) {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("Couldn't read {}: {}", path[1], e);
+                    return;
+                }
+            }
+        }
+        Some(_) => {
+            eprintln!("--input needs a path");
+            return;
+        }
+        None => INPUT.to_string(),
+    };
+
+    match args.first().map(String::as_str) {
+        Some("bench") => {
+            bench::run(args.get(1).and_then(|n| n.parse().ok()).unwrap_or(10_000));
+            return;
+        }
+        Some("generate") => {
+            generate::run(&args[1..]);
+            return;
+        }
+        _ => {}
+    }
+
+    let text = match load::load(&input, pad) {
+        Ok(text) => text,
+        Err(issues) => {
+            for issue in issues {
+                eprintln!("{}", issue);
+            }
+            return;
+        }
+    };
+    let schematic = match parse_input(&text) {
+        Ok(schematic) => schematic.with_adjacency(adjacency),
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+
+    match args.first().map(String::as_str) {
+        Some("components") => components::run(&schematic, &args[1..]),
+        Some("edit") => editor::run(schematic, &args[1..]),
+        Some("gears") => gears::run(&schematic, &args[1..]),
+        Some("export") => export::run(&schematic, &args[1..]),
+        Some("render") => render::run(&schematic, &args[1..]),
+        _ => {
+            part_1(&schematic);
+            part_2(&schematic);
+        }
+    }
 }
 
+#[derive(Debug, Clone)]
 struct Num {
     x: usize,
     y: usize,
     len: usize,
-    value: u32,
+    value: Value,
 }
 
-fn parse_input(input: &str) -> (Vec<Symbol>, Vec<Num>) {
-    let mut numbers: Vec<Num> = Vec::new();
-    let mut symbols: Vec<Symbol> = Vec::new();
-
-    input.lines().enumerate().for_each(|(y, l)| {
-        let mut current_num: Option<Num> = None;
-        l.chars().enumerate().for_each(|(x, c)| {
-            if let Some(d) = c.to_digit(10) {
-                if let Some(num) = &mut current_num {
-                    num.value = num.value * 10 + d;
-                    num.len += 1;
-                } else {
-                    current_num = Some(Num {
-                        x,
-                        y,
-                        len: 1,
-                        value: d,
-                    });
-                }
-            } else {
-                if current_num.is_some() {
-                    numbers.push(current_num.take().unwrap());
-                }
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SchematicError {
+    /// The `len` digit number at `pos` is too big for a `Value`.
+    LongNumber { pos: Pos, len: usize },
+    /// A gear ratio or one of the answers is too big for a `Value`.
+    Overflow(&'static str),
+}
 
-                if c != '.' {
-                    symbols.push(Symbol { x, y, value: c });
-                }
+impl Display for SchematicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchematicError::LongNumber { pos, len } => write!(
+                f,
+                "The {} digit number at {} doesn't fit in {} bits",
+                len,
+                pos,
+                Value::BITS
+            ),
+            SchematicError::Overflow(what) => {
+                write!(f, "The {} doesn't fit in {} bits", what, Value::BITS)
             }
-        });
+        }
+    }
+}
 
-        if let Some(num) = current_num {
-            numbers.push(num);
+struct Schematic {
+    grid: Grid<char>,
+    /// Every number, in reading order.
+    numbers: Vec<Num>,
+    /// Where each row's numbers start in `numbers`, with one extra entry for the end.
+    row_starts: Vec<usize>,
+    /// Which cells count as touching.
+    adjacency: Adjacency,
+}
+
+impl Schematic {
+    fn with_adjacency(self, adjacency: Adjacency) -> Schematic {
+        Schematic { adjacency, ..self }
+    }
+
+    /// Ids of every number touching `pos` under the schematic's adjacency.
+    ///
+    /// Only the numbers on the rows within reach of `pos` are looked at, and
+    /// unless the edges wrap those are found with a binary search, so this
+    /// doesn't depend on how big the schematic is.
+    fn numbers_around(&self, pos: Pos) -> impl Iterator<Item = usize> + '_ {
+        let adjacency = self.adjacency;
+        let width = self.grid.width();
+        let rows = adjacency.rows(pos.y, self.grid.height());
+
+        rows.into_iter().flat_map(move |(y, dy)| {
+            let reach = adjacency.reach(dy).unwrap_or(0);
+            let row = self.row_starts[y]..self.row_starts[y + 1];
+            let nums = &self.numbers[row.clone()];
+            let first = if adjacency.wrap {
+                0
+            } else {
+                nums.partition_point(|n| (n.x + n.len).saturating_add(reach) <= pos.x)
+            };
+
+            nums[first..]
+                .iter()
+                .enumerate()
+                .take_while(move |(_, n)| adjacency.wrap || n.x <= pos.x.saturating_add(reach))
+                .filter(move |(_, n)| adjacency.columns_apart(n.x, n.len, pos.x, width) <= reach)
+                .map(move |(i, _)| row.start + first + i)
+        })
+    }
+
+    /// Whether each number touches a symbol.
+    fn part_flags(&self) -> Vec<bool> {
+        let mut parts = vec![false; self.numbers.len()];
+        for (pos, _) in self.symbols() {
+            self.numbers_around(pos).for_each(|id| parts[id] = true);
         }
-    });
+        parts
+    }
 
-    (symbols, numbers)
+    fn symbols(&self) -> impl Iterator<Item = (Pos, char)> + '_ {
+        self.grid
+            .iter()
+            .filter(|(_, &c)| is_symbol(c))
+            .map(|(pos, &c)| (pos, c))
+    }
 }
 
-fn adjacent(s: &Symbol, n: &Num) -> bool {
-    s.x >= n.x.saturating_sub(1)
-        && s.x <= n.x + n.len
-        && s.y >= n.y.saturating_sub(1)
-        && s.y <= n.y + 1
+fn is_symbol(c: char) -> bool {
+    c != '.' && !c.is_ascii_digit()
 }
 
-fn part_1() {
-    let (symbols, numbers) = parse_input(INPUT);
+/// The numbers in `row`, which is row `y` of the grid, from left to right.
+fn tokenise(y: usize, row: &[char]) -> Result<Vec<Num>, SchematicError> {
+    let mut numbers = Vec::new();
+    let mut current_num: Option<Num> = None;
 
-    let sum: u32 = numbers
-        .into_iter()
-        .filter(|n| symbols.iter().any(|s| adjacent(s, n)))
-        .map(|thing| thing.value)
-        .sum();
+    for (x, c) in row.iter().enumerate() {
+        if let Some(d) = c.to_digit(10) {
+            if let Some(num) = &mut current_num {
+                num.value = num
+                    .value
+                    .checked_mul(10)
+                    .and_then(|v| v.checked_add(d as Value))
+                    .ok_or_else(|| SchematicError::LongNumber {
+                        pos: Pos::new(num.x, y),
+                        len: row[num.x..]
+                            .iter()
+                            .take_while(|c| c.is_ascii_digit())
+                            .count(),
+                    })?;
+                num.len += 1;
+            } else {
+                current_num = Some(Num {
+                    x,
+                    y,
+                    len: 1,
+                    value: d as Value,
+                });
+            }
+        } else if let Some(num) = current_num.take() {
+            numbers.push(num);
+        }
+    }
 
-    println!("Sum of part numbers: {}", sum);
+    if let Some(num) = current_num {
+        numbers.push(num);
+    }
+    Ok(numbers)
 }
 
-fn part_2() {
-    let (symbols, numbers) = parse_input(INPUT);
+/// Read a schematic, taking any short rows to end in empty cells.
+fn parse_input(input: &str) -> Result<Schematic, SchematicError> {
+    let grid = Grid::parse_padded(input, '.', |c| c);
+    let mut numbers: Vec<Num> = Vec::new();
+    let mut row_starts = Vec::with_capacity(grid.height() + 1);
+
+    for (y, row) in grid.rows().enumerate() {
+        row_starts.push(numbers.len());
+        numbers.extend(tokenise(y, row)?);
+    }
 
-    let sum: u32 = symbols
+    row_starts.push(numbers.len());
+
+    Ok(Schematic {
+        grid,
+        numbers,
+        row_starts,
+        adjacency: Adjacency::default(),
+    })
+}
+
+fn part_numbers(schematic: &Schematic) -> Result<Value, SchematicError> {
+    schematic
+        .numbers
         .iter()
-        .filter(|&s| s.value == '*')
-        .map(|s| {
-            numbers
-                .iter()
-                .filter(|&n| adjacent(s, n))
-                .collect::<Vec<_>>()
-        })
-        .filter(|nums| nums.len() == 2)
-        .map(|pair| pair[0].value * pair[1].value)
-        .sum();
+        .zip(schematic.part_flags())
+        .filter(|(_, part)| *part)
+        .try_fold(0 as Value, |sum, (thing, _)| sum.checked_add(thing.value))
+        .ok_or(SchematicError::Overflow("sum of part numbers"))
+}
+
+fn gear_ratios(schematic: &Schematic) -> Result<Value, SchematicError> {
+    gears::find_gears(schematic, &GearRule::default())
+        .iter()
+        .try_fold(0 as Value, |sum, gear| sum.checked_add(gear.value?))
+        .ok_or(SchematicError::Overflow("sum of gear ratios"))
+}
+
+fn part_1(schematic: &Schematic) {
+    match part_numbers(schematic) {
+        Ok(sum) => println!("Sum of part numbers: {}", sum),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn part_2(schematic: &Schematic) {
+    match gear_ratios(schematic) {
+        Ok(sum) => println!("Sum of gear ratios: {}", sum),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+#[test]
+fn sample() {
+    let schematic = parse_input(INPUT_SAMPLE).unwrap();
+
+    assert_eq!(part_numbers(&schematic), Ok(4361));
+    assert_eq!(gear_ratios(&schematic), Ok(467835));
+
+    let ragged = parse_input("467..\n...*\n..35\n").unwrap();
+    assert_eq!(ragged.grid.width(), 5);
+    assert_eq!(part_numbers(&ragged), Ok(467 + 35));
+}
+
+#[test]
+fn long_numbers() {
+    let digits = Value::MAX.to_string();
+    let schematic = parse_input(&format!("{}*\n{}.\n", digits, "1".repeat(digits.len()))).unwrap();
+    assert_eq!(schematic.numbers[0].value, Value::MAX);
+    assert_eq!(
+        part_numbers(&schematic),
+        Err(SchematicError::Overflow("sum of part numbers"))
+    );
+    assert_eq!(
+        gear_ratios(&schematic),
+        Err(SchematicError::Overflow("sum of gear ratios"))
+    );
+
+    let too_long = format!("..{}0.\n", digits);
+    assert!(matches!(
+        parse_input(&too_long),
+        Err(SchematicError::LongNumber { pos, len }) if pos == Pos::new(2, 0) && len == digits.len() + 1
+    ));
+}
+
+#[test]
+fn adjacencies() {
+    let parts = |text: &str, adjacency: &str| {
+        let schematic = parse_input(text)
+            .unwrap()
+            .with_adjacency(adjacency.parse().unwrap());
+        part_numbers(&schematic).unwrap()
+    };
+    let text = "1...2\n..*..\n.....\n3...4\n";
+
+    assert_eq!(parts(text, "chebyshev"), 0);
+    assert_eq!(parts(text, "chebyshev:2"), 10);
+    assert_eq!(parts(text, "manhattan:3"), 3);
+    assert_eq!(parts(text, "orthogonal:2"), 0);
+    assert_eq!(parts(text, "chebyshev:1,wrap"), 0);
+    assert_eq!(parts("..*..\n.....\n.....\n..7..\n", "chebyshev:1,wrap"), 7);
+    assert_eq!(parts(".*..1\n", "orthogonal:2,wrap"), 1);
+    assert_eq!(parts(".*..1\n", "orthogonal:2"), 0);
+    assert_eq!(parts(text, &format!("manhattan:{}", usize::MAX)), 10);
 
-    println!("Sum of gear ratios: {}", sum);
+    let sample = parse_input(INPUT_SAMPLE).unwrap();
+    assert_eq!(
+        part_numbers(&sample.with_adjacency("orthogonal".parse().unwrap())),
+        Ok(4361 - 467 - 592 - 755)
+    );
 }
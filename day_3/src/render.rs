@@ -0,0 +1,211 @@
+//! Printing the schematic with every cell coloured by what it turned out to be.
+
+use std::{io::IsTerminal, ops::Range};
+
+use crate::{
+    gears::{self, GearRule},
+    grid::{Grid, Pos},
+    Schematic,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    Empty,
+    /// A digit of a number touching a symbol.
+    Part,
+    /// A digit of a number touching nothing.
+    Loose,
+    Symbol,
+    Gear,
+}
+
+impl Class {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Class::Empty => "\x1b[2m",
+            Class::Part => "\x1b[32m",
+            Class::Loose => "\x1b[31m",
+            Class::Symbol => "\x1b[33m",
+            Class::Gear => "\x1b[1;35m",
+        }
+    }
+
+    fn marker(&self) -> char {
+        match self {
+            Class::Empty => ' ',
+            Class::Part => 'p',
+            Class::Loose => 'x',
+            Class::Symbol => 's',
+            Class::Gear => 'g',
+        }
+    }
+}
+
+/// What every cell of `schematic` is, with gears found by the puzzle's rule.
+pub fn classify(schematic: &Schematic) -> Grid<Class> {
+    let mut classes = schematic.grid.map(|&c| {
+        if crate::is_symbol(c) {
+            Class::Symbol
+        } else {
+            Class::Empty
+        }
+    });
+
+    for (num, part) in schematic.numbers.iter().zip(schematic.part_flags()) {
+        for x in num.x..num.x + num.len {
+            classes[Pos::new(x, num.y)] = if part { Class::Part } else { Class::Loose };
+        }
+    }
+    for gear in gears::find_gears(schematic, &GearRule::default()) {
+        classes[gear.pos] = Class::Gear;
+    }
+
+    classes
+}
+
+/// The part of the grid to print.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct View {
+    pub x: Range<usize>,
+    pub y: Range<usize>,
+}
+
+impl View {
+    pub fn all<T>(grid: &Grid<T>) -> View {
+        View {
+            x: 0..grid.width(),
+            y: 0..grid.height(),
+        }
+    }
+
+    /// Everything within `radius` cells of `pos`, cut down to fit in `grid`.
+    pub fn around<T>(grid: &Grid<T>, pos: Pos, radius: usize) -> View {
+        View {
+            x: pos.x.saturating_sub(radius)
+                ..pos
+                    .x
+                    .saturating_add(radius)
+                    .saturating_add(1)
+                    .min(grid.width()),
+            y: pos.y.saturating_sub(radius)
+                ..pos
+                    .y
+                    .saturating_add(radius)
+                    .saturating_add(1)
+                    .min(grid.height()),
+        }
+    }
+}
+
+/// The cells of `schematic` inside `view`, one line per row, each starting with its row number.
+///
+/// With `colour` off, every row is followed by a line of markers under the
+/// cells that aren't empty: `p` part, `x` not a part, `s` symbol, `g` gear.
+pub fn render(schematic: &Schematic, view: &View, colour: bool) -> String {
+    let classes = classify(schematic);
+    let margin = view.y.end.saturating_sub(1).to_string().len();
+    let mut out = String::new();
+
+    for y in view.y.clone() {
+        let cells = &schematic.grid.row(y)[view.x.clone()];
+        let row = &classes.row(y)[view.x.clone()];
+        out += &format!("{:>margin$} ", y);
+
+        if colour {
+            let mut current = None;
+            for (&c, class) in cells.iter().zip(row) {
+                if current != Some(class) {
+                    out += class.ansi();
+                    current = Some(class);
+                }
+                out.push(c);
+            }
+            out += "\x1b[0m\n";
+        } else {
+            out.extend(cells);
+            out.push('\n');
+
+            let markers: String = row.iter().map(Class::marker).collect();
+            if !markers.trim_end().is_empty() {
+                out += &format!("{:>margin$} {}\n", "", markers.trim_end());
+            }
+        }
+    }
+
+    out
+}
+
+/// `render [--at <x,y>] [--radius <r>] [--plain | --colour]`
+///
+/// Colour is on by default when printing to a terminal and `NO_COLOR` isn't set.
+pub fn run(schematic: &Schematic, args: &[String]) {
+    let mut at: Option<Pos> = None;
+    let mut radius = 10;
+    let mut colour = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.as_slice().first()) {
+            ("--plain", _) => colour = false,
+            ("--colour", _) => colour = true,
+            ("--at", Some(xy)) if parse_pos(xy).is_some() => {
+                at = parse_pos(xy);
+                args.next();
+            }
+            ("--radius", Some(r)) if r.parse::<usize>().is_ok() => {
+                radius = r.parse().unwrap();
+                args.next();
+            }
+            _ => {
+                eprintln!("Bad argument {:?}", arg);
+                return;
+            }
+        }
+    }
+
+    let view = match at {
+        Some(pos) if !schematic.grid.contains(pos) => {
+            eprintln!("{} is outside the schematic", pos);
+            return;
+        }
+        Some(pos) => View::around(&schematic.grid, pos, radius),
+        None => View::all(&schematic.grid),
+    };
+
+    println!(
+        "Columns {}..{}, rows {}..{}",
+        view.x.start, view.x.end, view.y.start, view.y.end
+    );
+    print!("{}", render(schematic, &view, colour));
+}
+
+fn parse_pos(s: &str) -> Option<Pos> {
+    let (x, y) = s.split_once(',')?;
+    Some(Pos::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+#[test]
+fn rendering() {
+    let schematic = crate::parse_input(crate::INPUT_SAMPLE).unwrap();
+
+    let classes = classify(&schematic);
+    assert_eq!(classes[Pos::new(5, 0)], Class::Loose);
+    assert_eq!(classes[Pos::new(0, 0)], Class::Part);
+    assert_eq!(classes[Pos::new(3, 1)], Class::Gear);
+    assert_eq!(classes[Pos::new(3, 4)], Class::Symbol);
+    assert_eq!(classes[Pos::new(0, 1)], Class::Empty);
+
+    let view = View::around(&schematic.grid, Pos::new(3, 1), 1);
+    assert_eq!(view, View { x: 2..5, y: 0..3 });
+    assert_eq!(
+        View::around(&schematic.grid, Pos::new(3, 3), usize::MAX),
+        View::all(&schematic.grid)
+    );
+    assert_eq!(
+        render(&schematic, &view, false),
+        "0 7..\n  p\n1 .*.\n   g\n2 35.\n  pp\n"
+    );
+
+    let coloured = render(&schematic, &view, true);
+    assert!(coloured.starts_with("0 \x1b[32m7\x1b[2m..\x1b[0m\n"));
+}